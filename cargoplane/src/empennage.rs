@@ -0,0 +1,297 @@
+use std::fmt;
+
+use na;
+use scad_generator::*;
+
+use sizing::AirframeLayout;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EmpennageType
+{
+    Conventional,
+    //The horizontal tail on top of the fin
+    TTail,
+    VTail,
+    InvertedV,
+}
+
+impl fmt::Display for EmpennageType
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        let name = match *self
+        {
+            EmpennageType::Conventional => "Conventional",
+            EmpennageType::TTail => "T-tail",
+            EmpennageType::VTail => "V-tail",
+            EmpennageType::InvertedV => "Inverted V",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+//A flat rectangular tail surface going out from `root`. `angle` is measured around the
+//x axis from the right side, so 0 is the right half of a horizontal tail and 90 is a fin.
+#[derive(Clone, Copy, Debug)]
+pub struct TailPanel
+{
+    pub root: na::Vector3<f32>,
+    pub angle: f32,
+    pub span: f32,
+    pub chord: f32,
+    pub x_offset: f32,
+}
+
+impl TailPanel
+{
+    pub fn area(&self) -> f32
+    {
+        self.span * self.chord
+    }
+
+    //Areas seen from above and from the side
+    pub fn projected_areas(&self) -> (f32, f32)
+    {
+        let angle = self.angle.to_radians();
+        (self.area() * angle.cos().abs(), self.area() * angle.sin().abs())
+    }
+
+    pub fn aerodynamic_center(&self) -> f32
+    {
+        self.x_offset + self.chord * 0.25
+    }
+
+    //Position of the tip of the panel relative to its root
+    fn tip(&self) -> na::Vector3<f32>
+    {
+        let angle = self.angle.to_radians();
+        vec3(0.0, angle.cos(), angle.sin()) * self.span
+    }
+
+    //A box along the panel starting at the root, `padding` larger than the panel in
+    //chord and thickness
+    fn slab(&self, length: f32, thickness: f32, padding: f32) -> ScadObject
+    {
+        scad!(Translate(vec3(self.x_offset, self.root.y, self.root.z));
+        {
+            scad!(Rotate(self.angle, vec3(1.0, 0.0, 0.0));
+            {
+                scad!(Translate(vec3(-padding, 0.0, -thickness / 2.0 - padding));
+                {
+                    scad!(Cube(vec3(self.chord + padding * 2.0, length, thickness + padding * 2.0)))
+                })
+            })
+        })
+    }
+
+    pub fn get(&self, thickness: f32) -> ScadObject
+    {
+        self.slab(self.span, thickness, 0.0)
+    }
+}
+
+//The tail surfaces of the plane sized from the horizontal and vertical tail of an
+//`AirframeLayout`. Every type has the same tail volumes as the conventional tail.
+pub struct Empennage
+{
+    pub empennage_type: EmpennageType,
+    pub panels: Vec<TailPanel>,
+    pub thickness: f32,
+
+    //Part of the chord behind the hinge line
+    pub control_fraction: f32,
+
+    //The root fittings hold `fitting_depth` of each panel with `fitting_wall` around it
+    pub fitting_depth: f32,
+    pub fitting_wall: f32,
+    pub fitting_clearance: f32,
+}
+
+impl Empennage
+{
+    pub fn new(layout: &AirframeLayout, empennage_type: EmpennageType) -> Empennage
+    {
+        let tail = layout.tail();
+        let fin = layout.fin();
+        let thickness = layout.thickness;
+
+        //The root of the horizontal tail is in the middle of the flat tail of the layout
+        let base = vec3(0.0, 0.0, thickness / 2.0);
+
+        let horizontal = |root: na::Vector3<f32>, angle: f32| TailPanel {
+            root,
+            angle,
+            span: tail.span / 2.0,
+            chord: tail.chord,
+            x_offset: tail.x_offset,
+        };
+
+        let vertical = TailPanel {
+            root: base,
+            angle: 90.0,
+            span: fin.span,
+            chord: fin.chord,
+            x_offset: fin.x_offset,
+        };
+
+        let panels = match empennage_type
+        {
+            EmpennageType::Conventional => vec!(horizontal(base, 0.0), horizontal(base, 180.0), vertical),
+            EmpennageType::TTail => {
+                let top = base + vec3(0.0, 0.0, fin.span);
+                vec!(vertical, horizontal(top, 0.0), horizontal(top, 180.0))
+            }
+            EmpennageType::VTail | EmpennageType::InvertedV => {
+                //The panels get the chord of the horizontal tail which gives them a
+                //different tail arm than the fin
+                let arm = tail.aerodynamic_center() - layout.wing().aerodynamic_center();
+                let wing = layout.wing();
+
+                let horizontal_area = layout.horizontal_tail_volume() * wing.area() * wing.chord / arm;
+                let vertical_area = layout.vertical_tail_volume() * wing.area() * wing.span / arm;
+
+                let dihedral = vertical_area.atan2(horizontal_area).to_degrees();
+                let panel_area = (horizontal_area.powi(2) + vertical_area.powi(2)).sqrt() / 2.0;
+
+                let angle = if empennage_type == EmpennageType::VTail {dihedral} else {-dihedral};
+
+                [angle, 180.0 - angle].iter()
+                    .map(|angle| TailPanel {
+                        root: base,
+                        angle: *angle,
+                        span: panel_area / tail.chord,
+                        chord: tail.chord,
+                        x_offset: tail.x_offset,
+                    })
+                    .collect()
+            }
+        };
+
+        Empennage {
+            empennage_type,
+            panels,
+            thickness,
+            control_fraction: 0.3,
+            fitting_depth: 30.0,
+            fitting_wall: 2.0,
+            fitting_clearance: 0.2,
+        }
+    }
+
+    //Sum of the projected areas of the panels times their tail arms
+    fn projected_moments(&self, wing_center: f32) -> (f32, f32)
+    {
+        self.panels.iter()
+            .map(|panel| {
+                let (horizontal, vertical) = panel.projected_areas();
+                let arm = panel.aerodynamic_center() - wing_center;
+                (horizontal * arm, vertical * arm)
+            })
+            .fold((0.0, 0.0), |sum, moment| (sum.0 + moment.0, sum.1 + moment.1))
+    }
+
+    pub fn horizontal_tail_volume(&self, layout: &AirframeLayout) -> f32
+    {
+        let wing = layout.wing();
+        self.projected_moments(wing.aerodynamic_center()).0 / (wing.area() * wing.chord)
+    }
+
+    pub fn vertical_tail_volume(&self, layout: &AirframeLayout) -> f32
+    {
+        let wing = layout.wing();
+        self.projected_moments(wing.aerodynamic_center()).1 / (wing.area() * wing.span)
+    }
+
+    //Projected control surface areas for pitch and yaw. On a V-tail both come from the
+    //same mixed ruddervators
+    pub fn control_surface_areas(&self) -> (f32, f32)
+    {
+        self.panels.iter()
+            .map(|panel| panel.projected_areas())
+            .fold((0.0, 0.0), |sum, area| {
+                (sum.0 + area.0 * self.control_fraction, sum.1 + area.1 * self.control_fraction)
+            })
+    }
+
+    pub fn get(&self) -> ScadObject
+    {
+        let mut result = scad!(Union);
+        for panel in &self.panels
+        {
+            result.add_child(panel.get(self.thickness));
+        }
+        result
+    }
+
+    //Panels that share a root point are held together by one fitting
+    fn roots(&self) -> Vec<na::Vector3<f32>>
+    {
+        let mut result: Vec<na::Vector3<f32>> = vec!();
+        for panel in &self.panels
+        {
+            if !result.iter().any(|root| (root - panel.root).norm() < 0.01)
+            {
+                result.push(panel.root);
+            }
+        }
+        result
+    }
+
+    //Sleeves around the roots of the panels with slots for the panels. A panel that
+    //starts at the tip of another panel, like the horizontal tail of a T-tail, is held by
+    //the same fitting as the tip of that panel
+    pub fn root_fittings(&self) -> Vec<ScadObject>
+    {
+        let thickness = self.thickness + self.fitting_clearance;
+
+        self.roots().into_iter()
+            .map(|root| {
+                let mut sleeves = scad!(Union);
+                let mut slots = scad!(Union);
+
+                for panel in &self.panels
+                {
+                    if (panel.root - root).norm() < 0.01
+                    {
+                        sleeves.add_child(panel.slab(self.fitting_depth, thickness, self.fitting_wall));
+                        slots.add_child(panel.slab(panel.span, thickness, 0.0));
+                    }
+                    //The panel ends at this root
+                    else if (panel.root + panel.tip() - root).norm() < 0.01
+                    {
+                        let end = TailPanel {
+                            root,
+                            angle: panel.angle + 180.0,
+                            ..*panel
+                        };
+                        sleeves.add_child(end.slab(self.fitting_depth, thickness, self.fitting_wall));
+                        slots.add_child(end.slab(self.fitting_depth, thickness, 0.0));
+                    }
+                }
+
+                scad!(Difference;{sleeves, slots})
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Empennage
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        writeln!(f, "{} empennage:", self.empennage_type)?;
+        for panel in &self.panels
+        {
+            writeln!(
+                f,
+                "  Panel at {:.1} degrees: span {:.0} mm, chord {:.0} mm",
+                panel.angle,
+                panel.span,
+                panel.chord
+            )?;
+        }
+
+        let (pitch, yaw) = self.control_surface_areas();
+        writeln!(f, "  Control surface area for pitch: {:.0} cm^2, yaw: {:.0} cm^2", pitch / 100.0, yaw / 100.0)
+    }
+}
@@ -5,12 +5,30 @@
 extern crate scad_generator;
 extern crate nalgebra as na;
 extern crate scad_util as su;
+extern crate rc_common;
 
 //Avoid having to write scad_generator:: everywhere
 use scad_generator::*;
 
+mod sizing;
+mod vlm;
+mod empennage;
+mod motor;
+
+use sizing::AirframeLayout;
+use rc_common::assembly::{Anchor, Assembly, Part};
+use rc_common::interference;
+use rc_common::servo::ServoSpec;
+use vlm::VlmSettings;
+use empennage::{Empennage, EmpennageType};
+use motor::MotorSpec;
+
 struct MotorPod
 {
+    motor: MotorSpec,
+    //Angles of the thrust line below and to the right of the pod axis in degrees
+    down_thrust: f32,
+    right_thrust: f32,
     outside_size: na::Vector3<f32>,
     screw_z_offset: f32,
     screw_locations: [f32;2],
@@ -18,19 +36,105 @@ struct MotorPod
     wall_thickness: f32,
 }
 
+//The height of the front wall that the motor is screwed to in wall thicknesses
+const MOTOR_WALL_MULTIPLIER: f32 = 3.0;
+//Smallest distance from the motor can to the edge of the front face
+const MOTOR_FACE_MARGIN: f32 = 1.0;
+
 impl MotorPod 
 {
-    pub fn new() -> MotorPod 
+    //The front of the pod grows to fit the motor and the mount moves back to make room
+    //for the end of the shaft and the ESC
+    pub fn new(motor: &MotorSpec) -> MotorPod 
     {
+        let wall_thickness = 2.0;
+        let screw_diameter = 3.6;
+
+        let face_size = motor.can_diameter.max(motor.mount_radius() * 2.0) + MOTOR_FACE_MARGIN * 2.0;
+
+        //The mounting block starts one screw diameter in front of the first screw
+        let esc_end = wall_thickness * MOTOR_WALL_MULTIPLIER + motor.rear_shaft_length + motor.esc_length;
+        let first_screw = esc_end + screw_diameter;
+
         MotorPod {
-            outside_size: vec3(140.0, 35.0, 30.0),
+            motor: *motor,
+            down_thrust: 0.0,
+            right_thrust: 0.0,
+            outside_size: vec3(140.0, face_size.max(35.0), face_size.max(30.0)),
             screw_z_offset: 6.0,
-            screw_locations: [50.0, 80.0],
-            screw_diameter: 3.6,
-            wall_thickness: 2.0,
+            screw_locations: [first_screw, first_screw + 30.0],
+            screw_diameter,
+            wall_thickness,
         }
     }
 
+    pub fn with_thrust_angles(mut self, down_thrust: f32, right_thrust: f32) -> MotorPod
+    {
+        self.down_thrust = down_thrust;
+        self.right_thrust = right_thrust;
+        self
+    }
+
+    //Rotates a direction from the pod axis to the motor axis. Right is +y when looking
+    //along the flight direction, which is -x
+    fn thrust_rotation(&self, direction: na::Vector3<f32>) -> na::Vector3<f32>
+    {
+        let (down, right) = (self.down_thrust.to_radians(), self.right_thrust.to_radians());
+
+        //Rotation by -down around y, then by -right around z
+        let direction = vec3(
+            direction.x * down.cos() - direction.z * down.sin(),
+            direction.y,
+            direction.x * down.sin() + direction.z * down.cos()
+        );
+        vec3(
+            direction.x * right.cos() + direction.y * right.sin(),
+            -direction.x * right.sin() + direction.y * right.cos(),
+            direction.z
+        )
+    }
+
+    //Middle of the tilted motor face. It is moved back far enough that the whole face
+    //stays behind the front of the pod
+    fn motor_face_center(&self) -> na::Vector3<f32>
+    {
+        let normal = self.thrust_rotation(vec3(1.0, 0.0, 0.0));
+        let setback = (normal.y.abs() * self.outside_size.y + normal.z.abs() * self.outside_size.z)
+            / 2.0 / normal.x;
+
+        vec3(setback, self.outside_size.y / 2.0, self.outside_size.z / 2.0)
+    }
+
+    //Places an object from the coordinates of the motor mount, where z goes into the pod
+    //from the motor face, onto the face
+    fn on_motor_face(&self, object: ScadObject) -> ScadObject
+    {
+        scad!(Translate(self.motor_face_center());
+        {
+            scad!(Rotate(-self.right_thrust, vec3(0.0, 0.0, 1.0));
+            {
+                scad!(Rotate(-self.down_thrust, vec3(0.0, 1.0, 0.0));
+                {
+                    scad!(Rotate(90.0, vec3(0.0, 1.0, 0.0));
+                    {
+                        object
+                    })
+                })
+            })
+        })
+    }
+
+    //Everything closer than `depth` to the motor face in front of it
+    fn in_front_of_face(&self, depth: f32) -> ScadObject
+    {
+        let size = self.outside_size.norm() * 2.0;
+
+        self.on_motor_face(scad!(Translate(vec3(-size / 2.0, -size / 2.0, depth - size));
+        {
+            scad!(Cube(vec3(size, size, size)))
+        }))
+    }
+
     fn get_shape(&self, shape_size: na::Vector3<f32>) -> ScadObject
     {
         let back_chamfer_length = 0.5;
@@ -80,7 +184,7 @@ impl MotorPod
     pub fn main_pod(&self) -> ScadObject 
     {
         //The height of the tabs that will go into the foam
-        let front_wall_multiplyer = 3.0;
+        let front_wall_multiplyer = MOTOR_WALL_MULTIPLIER;
 
         //Generating the actual pod
         let outside_shape = self.get_shape(self.outside_size);
@@ -89,18 +193,22 @@ impl MotorPod
             self.get_shape(self.outside_size - vec3(self.wall_thickness * (front_wall_multiplyer+2.0), self.wall_thickness * 2.0, 0.0))
         });
 
-        let motor_holes = scad!(Translate(vec3(0.0, self.outside_size.y / 2.0, self.outside_size.z / 2.0));
+        let front_wall = self.wall_thickness * front_wall_multiplyer;
+
+        //The front of the inside follows the motor face so the front wall keeps its thickness
+        let inside_shape = scad!(Difference;
         {
-            scad!(Rotate(90.0, vec3(0.0, 1.0, 0.0));
-            {
-                get_motor_holes(),
-            })
+            inside_shape,
+            self.in_front_of_face(front_wall)
         });
 
+        let motor_holes = self.on_motor_face(self.motor.mount_holes(front_wall));
+
 
         scad!(Difference;
         {
             outside_shape,
+            self.in_front_of_face(0.0),
             inside_shape,
             motor_holes,
             scad!(Translate(vec3(0.0, 0.0, self.outside_size.z - self.screw_z_offset));
@@ -194,6 +302,53 @@ impl MotorPod
             wire_holes,
         })
     }
+
+    //The motor face at the front of the pod
+    fn motor_anchor(&self) -> Anchor
+    {
+        Anchor::new(
+            self.motor_face_center(),
+            self.thrust_rotation(vec3(-1.0, 0.0, 0.0)),
+            self.thrust_rotation(vec3(0.0, 0.0, 1.0))
+        )
+    }
+
+    pub fn pod_part(&self) -> Part
+    {
+        //The open top of the pod which goes over the pod mount
+        let mount = Anchor::new(
+            vec3(0.0, self.outside_size.y / 2.0, self.outside_size.z),
+            vec3(0.0, 0.0, 1.0),
+            vec3(1.0, 0.0, 0.0)
+        );
+
+        Part::new("motor_pod", self.main_pod(), vec3(0.9, 0.5, 0.1))
+            .with_anchor("motor", self.motor_anchor())
+            .with_anchor("mount", mount)
+    }
+
+    pub fn mount_part(&self) -> Part
+    {
+        let top_thickness = 5.0;
+
+        //The top of the plate, where the mounting block goes into the pod
+        let pod = Anchor::new(
+            vec3(0.0, self.outside_size.y / 2.0, top_thickness),
+            vec3(0.0, 0.0, 1.0),
+            vec3(1.0, 0.0, 0.0)
+        );
+
+        //The bottom of the plate which rests on the carbon spar
+        let wing = Anchor::new(
+            vec3(self.outside_size.x / 2.0, self.outside_size.y / 2.0, 0.0),
+            vec3(0.0, 0.0, -1.0),
+            vec3(1.0, 0.0, 0.0)
+        );
+
+        Part::new("pod_mount", self.pod_mount(), vec3(0.3, 0.3, 0.3))
+            .with_anchor("pod", pod)
+            .with_anchor("wing", wing)
+    }
 }
 
 qstruct!{
@@ -375,54 +530,166 @@ fn body_screw_bar() -> ScadObject
     result
 }
 
-fn battery_tray() -> ScadObject
+qstruct!{
+    BatteryTray()
+    {
+        width: f32 = 45.0,
+        length: f32 = 145.0,
+        thickness: f32 = 7.0,
+        strap_width: f32 = 25.0,
+        strap_thickness: f32 = 5.0,
+        bottom_length: f32 = 45.0,
+        bottom_thickness: f32 = 10.0,
+
+        strap_locations: Vec<f32> = vec!(10.0, 75.0),
+
+        //Thickness of the velcro straps and the height of the battery they are cut for
+        strap_band_thickness: f32 = 2.0,
+        strap_loop_height: f32 = 26.0,
+    }
+}
+
+impl BatteryTray
 {
-    let width = 45.0;
-    let length = 145.0;
-    let thickness = 7.0;
-    let height = 10.0;
-    let strap_width = 25.0;
-    let strap_thickness = 5.0;
-    let bottom_length = 45.0;
-    let bottom_thickness = 10.0;
+    pub fn get(&self) -> ScadObject
+    {
+        let main_cube = scad!(Translate(vec3(0.0, 0.0, self.bottom_thickness));
+        {
+            scad!(Cube(vec3(self.length, self.width, self.thickness)))
+        });
+
+        let bottom_cube = scad!(Cube(vec3(self.bottom_length, self.width, self.bottom_thickness)));
+
+        let strap_holes = {
+            let mut result = scad!(Union);
 
-    let strap_locations = vec!(10.0, 75.0);
+            for pos in &self.strap_locations
+            {
+                result.add_child(scad!(Translate(vec3(*pos, 0.0, self.bottom_thickness));
+                {
+                    scad!(Cube(vec3(self.strap_width, self.width, self.strap_thickness)))
+                }));
+            }
 
+            result
+        };
 
-    let main_cube = scad!(Translate(vec3(0.0, 0.0, bottom_thickness));
+        scad!(Difference;
+        {
+            scad!(Union;
+                main_cube,
+                bottom_cube
+            ),
+            strap_holes
+        })
+    }
+
+    fn top(&self) -> f32
     {
-        scad!(Cube(vec3(length, width, thickness)))
-    });
+        self.bottom_thickness + self.thickness
+    }
+
+    pub fn part(&self) -> Part
+    {
+        //Centered between the straps on the top of the tray
+        let battery = Anchor::new(
+            vec3(
+                (self.strap_locations[0] + self.strap_locations[1] + self.strap_width) / 2.0,
+                self.width / 2.0,
+                self.top()
+            ),
+            vec3(0.0, 0.0, 1.0),
+            vec3(1.0, 0.0, 0.0)
+        );
 
-    let bottom_cube = scad!(Cube(vec3(bottom_length, width, bottom_thickness)));
+        let straps = Anchor::new(vec3(0.0, 0.0, 0.0), vec3(0.0, 0.0, 1.0), vec3(1.0, 0.0, 0.0));
 
-    let strap_holes = {
-        let mut result = scad!(Union);
+        let mut part = Part::new("battery_tray", self.get(), vec3(0.4, 0.4, 0.4))
+            .with_anchor("battery", battery)
+            .with_anchor("straps", straps)
+            .with_volume(vec3(0.0, 0.0, 0.0), vec3(self.bottom_length, self.width, self.bottom_thickness));
 
-        for pos in strap_locations
+        //The main plate is split into boxes around the strap holes
+        let mut start = 0.0;
+        for pos in &self.strap_locations
         {
-            result.add_child(scad!(Translate(vec3(pos, 0.0, bottom_thickness));
-            {
-                scad!(Cube(vec3(strap_width, width, strap_thickness)))
-            }));
+            part = part
+                .with_volume(
+                    vec3(start, 0.0, self.bottom_thickness),
+                    vec3(pos - start, self.width, self.thickness)
+                )
+                .with_volume(
+                    vec3(*pos, 0.0, self.bottom_thickness + self.strap_thickness),
+                    vec3(self.strap_width, self.width, self.thickness - self.strap_thickness)
+                );
+            start = pos + self.strap_width;
         }
+        part.with_volume(
+            vec3(start, 0.0, self.bottom_thickness),
+            vec3(self.length - start, self.width, self.thickness)
+        )
+    }
 
-        result
-    };
-
-    scad!(Difference;
+    //The straps going through the strap holes and around the battery. Only used
+    //for interference checks, in the coordinates of the tray
+    pub fn straps_part(&self) -> Part
     {
-        scad!(Union;
-            main_cube,
-            bottom_cube
-        ),
-        strap_holes
-    })
+        let band = self.strap_band_thickness;
+        let loop_top = self.top() + self.strap_loop_height;
+
+        let mut part = Part::new("battery_straps", scad!(Union), vec3(0.1, 0.1, 0.1))
+            .with_anchor(
+                "tray",
+                Anchor::new(vec3(0.0, 0.0, 0.0), vec3(0.0, 0.0, -1.0), vec3(1.0, 0.0, 0.0))
+            );
+
+        for pos in &self.strap_locations
+        {
+            part = part
+                //Through the strap hole
+                .with_volume(
+                    vec3(*pos, 0.0, self.bottom_thickness),
+                    vec3(self.strap_width, self.width, band)
+                )
+                //Up the sides
+                .with_volume(
+                    vec3(*pos, -band, self.bottom_thickness),
+                    vec3(self.strap_width, band, loop_top + band - self.bottom_thickness)
+                )
+                .with_volume(
+                    vec3(*pos, self.width, self.bottom_thickness),
+                    vec3(self.strap_width, band, loop_top + band - self.bottom_thickness)
+                )
+                //Over the battery
+                .with_volume(
+                    vec3(*pos, -band, loop_top),
+                    vec3(self.strap_width, self.width + band * 2.0, band)
+                );
+        }
+        part
+    }
 }
 
-fn get_motor_holes() -> ScadObject 
+fn battery_part(size: na::Vector3<f32>) -> Part
 {
-    su::radio_control::generic_motor_holes(16.0, 19.0, 3.5)
+    let tray = Anchor::new(
+        vec3(size.x / 2.0, size.y / 2.0, 0.0),
+        vec3(0.0, 0.0, -1.0),
+        vec3(1.0, 0.0, 0.0)
+    );
+
+    Part::new("battery", scad!(Cube(size)), vec3(0.8, 0.1, 0.1))
+        .with_anchor("tray", tray)
+        .with_volume(vec3(0.0, 0.0, 0.0), size)
+}
+
+fn battery_assembly(battery_size: na::Vector3<f32>) -> Assembly
+{
+    let tray = BatteryTray::new();
+
+    Assembly::new(tray.part())
+        .mate(tray.straps_part(), "tray", "battery_tray", "straps")
+        .mate(battery_part(battery_size), "tray", "battery_tray", "battery")
 }
 
 qstruct!{
@@ -502,37 +769,134 @@ impl ControllerMount
             }),
         })
     }
+
+    //Split plane between the top and bottom part at the screw axes
+    fn screw_anchor(&self, x: f32, normal_z: f32) -> Anchor
+    {
+        Anchor::new(
+            vec3(x, self.outside_part_length / 2., self.bottom_thickness - 1.),
+            vec3(0., 0., normal_z),
+            vec3(0., 1., 0.)
+        )
+    }
+
+    pub fn front_top_part(&self) -> Part
+    {
+        Part::new("controller_top", self.get_front_top(), vec3(0.2, 0.4, 0.8))
+            .with_anchor("left_screw", self.screw_anchor(self.side_padding, -1.))
+            .with_anchor("right_screw", self.screw_anchor(self.side_padding + self.board_width, -1.))
+    }
+
+    pub fn front_bottom_part(&self) -> Part
+    {
+        Part::new("controller_bottom", self.get_front_bottom(), vec3(0.2, 0.6, 0.9))
+            .with_anchor("left_screw", self.screw_anchor(self.side_padding, 1.))
+            .with_anchor("right_screw", self.screw_anchor(self.side_padding + self.board_width, 1.))
+    }
 }
 
 
-fn wings() -> ScadObject 
+fn wings(layout: &AirframeLayout) -> ScadObject 
 {
-    let wing_front_offset = 100.0;
-    let wingspan = 1600.0;
-    let wing_width = 230.0;
-
-    let tailspan = 520.0;
-    let tail_width = 220.0;
-    let thickness = 6.0;
-    
-    let total_length = 750.0;
+    let wing = layout.wing();
+    let tail = layout.tail();
+    let thickness = layout.thickness;
 
     scad!(Union;
     {
         //Main wing
-        scad!(Translate(vec3(wing_front_offset, -wingspan/2.0, 0.0));
+        scad!(Translate(vec3(wing.x_offset, -wing.span/2.0, 0.0));
         {
-            scad!(Cube(vec3(wing_width, wingspan, thickness))),
+            scad!(Cube(vec3(wing.chord, wing.span, thickness))),
         }),
 
         //Tail wingss
-        scad!(Translate(vec3(total_length - tail_width, -tailspan / 2.0, 0.0));
+        scad!(Translate(vec3(tail.x_offset, -tail.span / 2.0, 0.0));
         {
-            scad!(Cube(vec3(tail_width, tailspan, thickness)))
+            scad!(Cube(vec3(tail.chord, tail.span, thickness)))
         })
     })
 }
 
+//Cuts a bay for an aileron servo in each wing half. The mounting frame is glued into
+//the bay flush with the top of the wing and the servo hangs below it
+fn wings_with_servo_bays(layout: &AirframeLayout, servo: &ServoSpec) -> ScadObject
+{
+    let wing = layout.wing();
+    let wall_thickness = 2.0;
+
+    let bay_x = wing.x_offset + wing.chord * 0.6;
+    let bay_positions = [-wing.span * 0.35, wing.span * 0.35];
+
+    let mut bays = scad!(Union);
+    let mut frames = scad!(Union);
+    for y in &bay_positions
+    {
+        let placement = vec3(bay_x, y - servo.body_size.y / 2.0, layout.thickness - servo.tab_height);
+
+        bays.add_child(scad!(Translate(placement);
+        {
+            servo.frame_outline(wall_thickness, layout.thickness)
+        }));
+        frames.add_child(scad!(Translate(placement);
+        {
+            servo.mounting_frame(wall_thickness, layout.thickness)
+        }));
+    }
+
+    scad!(Union;
+    {
+        scad!(Difference;{wings(layout), bays}),
+        frames
+    })
+}
+
+fn wings_part(layout: &AirframeLayout) -> Part
+{
+    let wing = layout.wing();
+
+    //The pod mount sits on top of the carbon spar at the middle of the wing
+    let pod_mount = Anchor::new(
+        vec3(wing.x_offset + wing.chord / 2.0, 0.0, layout.thickness),
+        vec3(0.0, 0.0, 1.0),
+        vec3(1.0, 0.0, 0.0)
+    );
+
+    Part::new("wings", wings_with_servo_bays(layout, &ServoSpec::slim_wing()), vec3(0.9, 0.9, 0.8))
+        .with_anchor("pod_mount", pod_mount)
+}
+
+//The two halves of the controller mount clamped around the board
+fn controller_mount_assembly() -> Assembly
+{
+    let mount = ControllerMount::new();
+
+    Assembly::new(mount.front_bottom_part())
+        .mate(mount.front_top_part(), "left_screw", "controller_bottom", "left_screw")
+}
+
+//Name of the motor in the motor catalog
+const MOTOR: &str = "2212";
+
+//The motor the pod is built around. Looked up once here so a name that is not in the
+//catalog is reported where it is chosen
+fn chosen_motor() -> MotorSpec
+{
+    MotorSpec::by_name(MOTOR).unwrap_or_else(|| {
+        let names = MotorSpec::catalog().iter().map(|spec| spec.name).collect::<Vec<_>>();
+        panic!("Unknown motor {}, the catalog has {}", MOTOR, names.join(", "))
+    })
+}
+
+fn cargoplane_assembly(layout: &AirframeLayout) -> Assembly
+{
+    let motor_pod = MotorPod::new(&chosen_motor()).with_thrust_angles(2.0, 2.0);
+
+    Assembly::new(wings_part(layout))
+        .mate(motor_pod.mount_part(), "wing", "wings", "pod_mount")
+        .mate(motor_pod.pod_part(), "mount", "pod_mount", "pod")
+}
+
 fn nose_attacher() -> ScadObject
 {
     let height = 10.0;
@@ -678,13 +1042,9 @@ pub fn main()
 
     sfile.set_detail(50);
     //sfile.add_object(translation);
-    //sfile.add_object(MotorPod::new().main_pod());
+    //sfile.add_object(MotorPod::new(&chosen_motor()).main_pod());
 
-    //sfile.add_object(scad!(Translate(vec3(0.0, 0.0, 50.0));
-    //{
-    //    MotorPod::new().pod_mount(),
-    //}));
-    //sfile.add_object(MotorPod::new().pod_mount());
+    //sfile.add_object(MotorPod::new(&chosen_motor()).pod_mount());
     //sfile.add_object(
     //    scad!(Difference;
     //    {
@@ -692,7 +1052,7 @@ pub fn main()
     //        scad!(Cube(vec3(750.0, 1000.0, 1000.0))),
     //    }));
     //sfile.add_object(body_screw_bar());
-    //sfile.add_object(battery_tray());
+    //sfile.add_object(BatteryTray::new().get());
     //sfile.add_object(nose_attacher());
     //sfile.add_object(WingStrutHolder::new().get_back_part());
 
@@ -704,4 +1064,55 @@ pub fn main()
     sfile.add_object(ControllerMount::new().get_front_bottom());
 
     sfile.write_to_file(String::from("cargo_auto.scad"));
+
+    let layout = AirframeLayout::new();
+    let assembly = cargoplane_assembly(&layout);
+    assembly.write_to_file("cargo_assembly.scad");
+    assembly.write_animation(60.0, "cargo_assembly_animation.scad");
+    print!("{}", MotorPod::new(&chosen_motor()).motor);
+
+    let mut exploded_file = ScadFile::new();
+    exploded_file.set_detail(50);
+    exploded_file.add_object(controller_mount_assembly().exploded(15.0));
+    exploded_file.write_to_file(String::from("controller_mount_exploded.scad"));
+
+    //A 3S 2200 mAh pack
+    let batteries = battery_assembly(vec3(105.0, 34.0, 24.0));
+    for interference in interference::find_interferences(&batteries)
+    {
+        println!("{}", interference);
+    }
+    match interference::clearance(&batteries, "battery", "battery_straps")
+    {
+        Some(clearance) => println!("Battery clearance to straps: {:.2} mm", clearance),
+        None => println!("Battery clearance to straps not checked, a part has no collision volumes"),
+    }
+
+    for empennage_type in &[EmpennageType::Conventional, EmpennageType::TTail, EmpennageType::VTail, EmpennageType::InvertedV]
+    {
+        let empennage = Empennage::new(&layout, *empennage_type);
+        print!("{}", empennage);
+        println!(
+            "  Tail volumes: horizontal {:.3}, vertical {:.3}",
+            empennage.horizontal_tail_volume(&layout),
+            empennage.vertical_tail_volume(&layout)
+        );
+
+        let mut tail_file = ScadFile::new();
+        tail_file.set_detail(50);
+        tail_file.add_object(empennage.get());
+        for fitting in empennage.root_fittings()
+        {
+            tail_file.add_object(fitting);
+        }
+        tail_file.write_to_file(format!("cargo_tail_{:?}.scad", empennage_type).to_lowercase());
+    }
+
+    let sizing = layout.sizing_report();
+    print!("{}", sizing);
+    match vlm::analyse(&layout, &VlmSettings::new(), sizing.recommended_cg)
+    {
+        Ok(analysis) => print!("{}", analysis),
+        Err(error) => println!("VLM analysis failed: {}", error),
+    }
 }
@@ -0,0 +1,162 @@
+use std::fmt;
+
+use scad_generator::*;
+
+//Dimensions of an outrunner motor and its mount in mm. The mount has two pairs of
+//screws across each other, one pair along x and one along y. Many motors use the same
+//distance for both.
+#[derive(Clone, Copy, Debug)]
+pub struct MotorSpec
+{
+    pub name: &'static str,
+
+    //Distance between the screws of each pair
+    pub bolt_circles: [f32; 2],
+    pub screw_diameter: f32,
+    //Hole in the middle of the mount for the bearing boss and the shaft
+    pub shaft_clearance: f32,
+    //How far the shaft and circlip stick out behind the mount
+    pub rear_shaft_length: f32,
+    //Length of an ESC for the motor. It goes in the pod behind the end of the shaft with
+    //its wires
+    pub esc_length: f32,
+
+    pub can_diameter: f32,
+    pub can_length: f32,
+
+    //Recommended props as (diameter, pitch) in inches
+    pub props: &'static [(f32, f32)],
+}
+
+impl MotorSpec
+{
+    pub fn size_1806() -> MotorSpec
+    {
+        MotorSpec {
+            name: "1806",
+            bolt_circles: [12.0, 12.0],
+            screw_diameter: 2.5,
+            shaft_clearance: 6.0,
+            rear_shaft_length: 4.0,
+            esc_length: 24.0,
+            can_diameter: 23.0,
+            can_length: 15.0,
+            props: &[(5.0, 3.0), (6.0, 3.0)],
+        }
+    }
+
+    pub fn size_2208() -> MotorSpec
+    {
+        MotorSpec {
+            name: "2208",
+            bolt_circles: [16.0, 19.0],
+            screw_diameter: 3.5,
+            shaft_clearance: 8.0,
+            rear_shaft_length: 5.0,
+            esc_length: 30.0,
+            can_diameter: 28.0,
+            can_length: 20.0,
+            props: &[(8.0, 4.0), (9.0, 4.7)],
+        }
+    }
+
+    //The motor the pod was first designed around
+    pub fn size_2212() -> MotorSpec
+    {
+        MotorSpec {
+            name: "2212",
+            bolt_circles: [16.0, 19.0],
+            screw_diameter: 3.5,
+            shaft_clearance: 8.0,
+            rear_shaft_length: 6.0,
+            esc_length: 34.0,
+            can_diameter: 27.5,
+            can_length: 26.0,
+            props: &[(9.0, 6.0), (10.0, 4.5)],
+        }
+    }
+
+    pub fn size_2306() -> MotorSpec
+    {
+        MotorSpec {
+            name: "2306",
+            bolt_circles: [16.0, 16.0],
+            screw_diameter: 3.5,
+            shaft_clearance: 7.0,
+            rear_shaft_length: 3.0,
+            esc_length: 30.0,
+            can_diameter: 28.0,
+            can_length: 18.0,
+            props: &[(5.0, 4.3), (6.0, 4.0)],
+        }
+    }
+
+    pub fn size_2814() -> MotorSpec
+    {
+        MotorSpec {
+            name: "2814",
+            bolt_circles: [19.0, 25.0],
+            screw_diameter: 3.5,
+            shaft_clearance: 10.0,
+            rear_shaft_length: 8.0,
+            esc_length: 45.0,
+            can_diameter: 35.0,
+            can_length: 30.0,
+            props: &[(11.0, 4.7), (12.0, 6.0)],
+        }
+    }
+
+    pub fn catalog() -> Vec<MotorSpec>
+    {
+        vec!(
+            MotorSpec::size_1806(),
+            MotorSpec::size_2208(),
+            MotorSpec::size_2212(),
+            MotorSpec::size_2306(),
+            MotorSpec::size_2814(),
+        )
+    }
+
+    pub fn by_name(name: &str) -> Option<MotorSpec>
+    {
+        MotorSpec::catalog().into_iter().find(|spec| spec.name == name)
+    }
+
+    //Radius of the mount including the screw holes
+    pub fn mount_radius(&self) -> f32
+    {
+        self.bolt_circles[0].max(self.bolt_circles[1]) / 2.0 + self.screw_diameter / 2.0
+    }
+
+    //The screw holes and the shaft hole going through a wall `thickness` thick along z
+    //with the middle of the motor at the origin
+    pub fn mount_holes(&self, thickness: f32) -> ScadObject
+    {
+        let hole = |x: f32, y: f32, diameter: f32| scad!(Translate(vec3(x, y, -1.0));
+        {
+            scad!(Cylinder(thickness + 2.0, Diameter(diameter)))
+        });
+
+        let mut result = scad!(Union;{hole(0.0, 0.0, self.shaft_clearance)});
+
+        for side in &[-1.0, 1.0]
+        {
+            result.add_child(hole(side * self.bolt_circles[0] / 2.0, 0.0, self.screw_diameter));
+            result.add_child(hole(0.0, side * self.bolt_circles[1] / 2.0, self.screw_diameter));
+        }
+        result
+    }
+}
+
+impl fmt::Display for MotorSpec
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "{} motor, {}x{} mm can, props:", self.name, self.can_diameter, self.can_length)?;
+        for &(diameter, pitch) in self.props
+        {
+            write!(f, " {}x{}", diameter, pitch)?;
+        }
+        writeln!(f)
+    }
+}
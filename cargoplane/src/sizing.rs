@@ -0,0 +1,270 @@
+use std::fmt;
+
+//A flat rectangular lifting surface. All lengths are in mm, `x_offset` is the position
+//of the leading edge measured from the nose.
+#[derive(Clone, Copy, Debug)]
+pub struct Surface
+{
+    pub span: f32,
+    pub chord: f32,
+    pub x_offset: f32,
+}
+
+impl Surface
+{
+    pub fn area(&self) -> f32
+    {
+        self.span * self.chord
+    }
+
+    pub fn aspect_ratio(&self) -> f32
+    {
+        self.span * self.span / self.area()
+    }
+
+    //The aerodynamic center of a rectangular surface is at a quarter chord
+    pub fn aerodynamic_center(&self) -> f32
+    {
+        self.x_offset + self.chord * 0.25
+    }
+
+    //Lift curve slope per radian using the Helmbold equation for low aspect ratios
+    pub fn lift_slope(&self) -> f32
+    {
+        let ar = self.aspect_ratio();
+        2.0 * std::f32::consts::PI * ar / (2.0 + (ar * ar + 4.0).sqrt())
+    }
+}
+
+qstruct!{
+    AirframeLayout()
+    {
+        wing_front_offset: f32 = 100.0,
+        wingspan: f32 = 1600.0,
+        wing_width: f32 = 230.0,
+
+        tailspan: f32 = 520.0,
+        tail_width: f32 = 220.0,
+
+        fin_height: f32 = 180.0,
+        fin_width: f32 = 180.0,
+
+        thickness: f32 = 6.0,
+
+        total_length: f32 = 750.0,
+    }
+}
+
+//Usual ranges for small RC aircraft
+const ASPECT_RATIO_RANGE: (f32, f32) = (5.0, 12.0);
+const HORIZONTAL_VOLUME_RANGE: (f32, f32) = (0.35, 0.8);
+const VERTICAL_VOLUME_RANGE: (f32, f32) = (0.02, 0.05);
+
+//Fraction of the free stream dynamic pressure seen by the tail
+const TAIL_EFFICIENCY: f32 = 0.9;
+//Wanted distance between the CG and the neutral point as a fraction of the MAC
+const STATIC_MARGIN: f32 = 0.1;
+
+impl AirframeLayout
+{
+    pub fn wing(&self) -> Surface
+    {
+        Surface {
+            span: self.wingspan,
+            chord: self.wing_width,
+            x_offset: self.wing_front_offset,
+        }
+    }
+
+    pub fn tail(&self) -> Surface
+    {
+        Surface {
+            span: self.tailspan,
+            chord: self.tail_width,
+            x_offset: self.total_length - self.tail_width,
+        }
+    }
+
+    //The fin is treated as a surface whose span is its height
+    pub fn fin(&self) -> Surface
+    {
+        Surface {
+            span: self.fin_height,
+            chord: self.fin_width,
+            x_offset: self.total_length - self.fin_width,
+        }
+    }
+
+    pub fn horizontal_tail_volume(&self) -> f32
+    {
+        let wing = self.wing();
+        let tail = self.tail();
+
+        let arm = tail.aerodynamic_center() - wing.aerodynamic_center();
+
+        tail.area() * arm / (wing.area() * wing.chord)
+    }
+
+    pub fn vertical_tail_volume(&self) -> f32
+    {
+        let wing = self.wing();
+        let fin = self.fin();
+
+        let arm = fin.aerodynamic_center() - wing.aerodynamic_center();
+
+        fin.area() * arm / (wing.area() * wing.span)
+    }
+
+    //The downwash gradient behind the wing, assuming an elliptical lift distribution
+    pub fn downwash_gradient(&self) -> f32
+    {
+        let wing = self.wing();
+        2.0 * wing.lift_slope() / (std::f32::consts::PI * wing.aspect_ratio())
+    }
+
+    //Position of the neutral point measured from the nose
+    pub fn neutral_point(&self) -> f32
+    {
+        let wing = self.wing();
+        let tail = self.tail();
+
+        let slope_ratio = tail.lift_slope() / wing.lift_slope();
+
+        wing.aerodynamic_center()
+            + wing.chord
+                * self.horizontal_tail_volume()
+                * TAIL_EFFICIENCY
+                * slope_ratio
+                * (1.0 - self.downwash_gradient())
+    }
+
+    pub fn sizing_report(&self) -> SizingReport
+    {
+        let wing = self.wing();
+        let neutral_point = self.neutral_point();
+
+        let mut warnings = vec!();
+
+        let aspect_ratio = wing.aspect_ratio();
+        if aspect_ratio < ASPECT_RATIO_RANGE.0 || aspect_ratio > ASPECT_RATIO_RANGE.1
+        {
+            warnings.push(SizingWarning::AspectRatio(aspect_ratio));
+        }
+
+        //The proposed sizes bring the tail volumes to the closest end of the range
+        let horizontal_volume = self.horizontal_tail_volume();
+        if horizontal_volume < HORIZONTAL_VOLUME_RANGE.0 || horizontal_volume > HORIZONTAL_VOLUME_RANGE.1
+        {
+            let target = horizontal_volume.max(HORIZONTAL_VOLUME_RANGE.0).min(HORIZONTAL_VOLUME_RANGE.1);
+            warnings.push(SizingWarning::HorizontalTailVolume(horizontal_volume, self.tailspan_for_volume(target)));
+        }
+
+        let vertical_volume = self.vertical_tail_volume();
+        if vertical_volume < VERTICAL_VOLUME_RANGE.0 || vertical_volume > VERTICAL_VOLUME_RANGE.1
+        {
+            let target = vertical_volume.max(VERTICAL_VOLUME_RANGE.0).min(VERTICAL_VOLUME_RANGE.1);
+            warnings.push(SizingWarning::VerticalTailVolume(vertical_volume, self.fin_height_for_volume(target)));
+        }
+
+        SizingReport {
+            wing_area: wing.area(),
+            aspect_ratio,
+            horizontal_tail_volume: horizontal_volume,
+            vertical_tail_volume: vertical_volume,
+            neutral_point,
+            recommended_cg: neutral_point - STATIC_MARGIN * wing.chord,
+            warnings,
+        }
+    }
+
+    //The tailspan that gives a horizontal tail volume of `target`.
+    //The tail chord is kept since it decides the tail arm
+    pub fn tailspan_for_volume(&self, target: f32) -> f32
+    {
+        let wing = self.wing();
+        let arm = self.tail().aerodynamic_center() - wing.aerodynamic_center();
+
+        let area = target * wing.area() * wing.chord / arm;
+
+        area / self.tail_width
+    }
+
+    //The fin height that gives a vertical tail volume of `target`
+    pub fn fin_height_for_volume(&self, target: f32) -> f32
+    {
+        let wing = self.wing();
+        let arm = self.fin().aerodynamic_center() - wing.aerodynamic_center();
+
+        let area = target * wing.area() * wing.span / arm;
+
+        area / self.fin_width
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum SizingWarning
+{
+    AspectRatio(f32),
+    //The volume and the tailspan that would fix it
+    HorizontalTailVolume(f32, f32),
+    //The volume and the fin height that would fix it
+    VerticalTailVolume(f32, f32),
+}
+
+impl fmt::Display for SizingWarning
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match *self
+        {
+            SizingWarning::AspectRatio(value) =>
+                write!(f, "Aspect ratio {:.2} is outside {:?}", value, ASPECT_RATIO_RANGE),
+            SizingWarning::HorizontalTailVolume(value, tailspan) =>
+                write!(
+                    f,
+                    "Horizontal tail volume {:.3} is outside {:?}, try a tailspan of {:.0} mm",
+                    value,
+                    HORIZONTAL_VOLUME_RANGE,
+                    tailspan
+                ),
+            SizingWarning::VerticalTailVolume(value, fin_height) =>
+                write!(
+                    f,
+                    "Vertical tail volume {:.3} is outside {:?}, try a fin height of {:.0} mm",
+                    value,
+                    VERTICAL_VOLUME_RANGE,
+                    fin_height
+                ),
+        }
+    }
+}
+
+pub struct SizingReport
+{
+    pub wing_area: f32,
+    pub aspect_ratio: f32,
+    pub horizontal_tail_volume: f32,
+    pub vertical_tail_volume: f32,
+    pub neutral_point: f32,
+    pub recommended_cg: f32,
+    pub warnings: Vec<SizingWarning>,
+}
+
+impl fmt::Display for SizingReport
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        writeln!(f, "Wing area: {:.0} cm^2", self.wing_area / 100.0)?;
+        writeln!(f, "Aspect ratio: {:.2}", self.aspect_ratio)?;
+        writeln!(f, "Horizontal tail volume: {:.3}", self.horizontal_tail_volume)?;
+        writeln!(f, "Vertical tail volume: {:.3}", self.vertical_tail_volume)?;
+        writeln!(f, "Neutral point: {:.1} mm", self.neutral_point)?;
+        writeln!(f, "Recommended CG: {:.1} mm", self.recommended_cg)?;
+
+        for warning in &self.warnings
+        {
+            writeln!(f, "Warning: {}", warning)?;
+        }
+        Ok(())
+    }
+}
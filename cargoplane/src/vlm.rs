@@ -0,0 +1,470 @@
+use std::fmt;
+
+use na;
+use scad_generator::*;
+
+use sizing::{AirframeLayout, Surface};
+
+//Vortex lattice analysis of the wing and tail planforms in `AirframeLayout`.
+//
+//Each surface is split into a grid of panels with a horseshoe vortex whose bound leg sits
+//at the panel quarter chord. The flow tangency condition is enforced at the three quarter
+//chord point of each panel. The surfaces are flat plates parallel to the z=0 plane, with
+//the tail `tail_height` above the wing, and the angles are assumed to be small, so the
+//solution is linear in the angle of attack.
+
+qstruct!{
+    VlmSettings()
+    {
+        wing_spanwise_panels: usize = 24,
+        tail_spanwise_panels: usize = 12,
+        chordwise_panels: usize = 4,
+
+        //Incidences in degrees relative to the fuselage axis. A cambered wing can be
+        //modelled by adding its zero lift angle to the incidence
+        wing_incidence: f32 = 2.0,
+        tail_incidence: f32 = 0.0,
+
+        //Height of the tail above the wing
+        tail_height: f32 = 50.0,
+    }
+}
+
+//Radius of the smoothed core of the vortices. The velocity goes smoothly to zero inside
+//the core instead of growing without bound close to the vortex lines
+const VORTEX_CORE_RADIUS: f32 = 5.0;
+
+//Pivots smaller than this make the influence matrix singular
+const SINGULAR_PIVOT: f32 = 1e-12;
+
+//Length of the trailing legs of the horseshoes as a multiple of the largest span
+const TRAILING_LEG_FACTOR: f32 = 100.0;
+
+struct Panel
+{
+    //Ends of the bound vortex, `left` has the lower y value
+    left: na::Vector3<f32>,
+    right: na::Vector3<f32>,
+    control_point: na::Vector3<f32>,
+
+    incidence: f32,
+    strip: usize,
+}
+
+struct Strip
+{
+    left: f32,
+    right: f32,
+    height: f32,
+    chord: f32,
+    is_wing: bool,
+}
+
+impl Strip
+{
+    fn center(&self) -> f32
+    {
+        (self.left + self.right) / 2.0
+    }
+
+    fn width(&self) -> f32
+    {
+        self.right - self.left
+    }
+}
+
+struct Lattice
+{
+    panels: Vec<Panel>,
+    strips: Vec<Strip>,
+    trailing_length: f32,
+}
+
+impl Lattice
+{
+    fn new(layout: &AirframeLayout, settings: &VlmSettings) -> Lattice
+    {
+        let mut lattice = Lattice {
+            panels: vec!(),
+            strips: vec!(),
+            trailing_length: layout.wingspan.max(layout.tailspan) * TRAILING_LEG_FACTOR,
+        };
+
+        lattice.add_surface(
+            &layout.wing(),
+            settings.wing_spanwise_panels,
+            settings.chordwise_panels,
+            settings.wing_incidence.to_radians(),
+            0.0,
+            true
+        );
+        lattice.add_surface(
+            &layout.tail(),
+            settings.tail_spanwise_panels,
+            settings.chordwise_panels,
+            settings.tail_incidence.to_radians(),
+            settings.tail_height,
+            false
+        );
+
+        lattice
+    }
+
+    fn add_surface(
+        &mut self,
+        surface: &Surface,
+        spanwise: usize,
+        chordwise: usize,
+        incidence: f32,
+        height: f32,
+        is_wing: bool
+    )
+    {
+        let panel_width = surface.span / spanwise as f32;
+        let panel_chord = surface.chord / chordwise as f32;
+
+        for i in 0..spanwise
+        {
+            let left = -surface.span / 2.0 + i as f32 * panel_width;
+            let right = left + panel_width;
+
+            let strip = self.strips.len();
+            self.strips.push(Strip {
+                left,
+                right,
+                height,
+                chord: surface.chord,
+                is_wing
+            });
+
+            for j in 0..chordwise
+            {
+                let leading_edge = surface.x_offset + j as f32 * panel_chord;
+                let bound_x = leading_edge + panel_chord * 0.25;
+
+                self.panels.push(Panel {
+                    left: vec3(bound_x, left, height),
+                    right: vec3(bound_x, right, height),
+                    control_point: vec3(leading_edge + panel_chord * 0.75, (left + right) / 2.0, height),
+                    incidence,
+                    strip
+                });
+            }
+        }
+    }
+
+    //Velocity induced at `point` by the horseshoe vortex of `panel` with unit strength
+    fn induced_velocity(&self, panel: &Panel, point: na::Vector3<f32>) -> na::Vector3<f32>
+    {
+        let trailing = vec3(self.trailing_length, 0.0, 0.0);
+
+        segment_velocity(panel.left + trailing, panel.left, point)
+            + segment_velocity(panel.left, panel.right, point)
+            + segment_velocity(panel.right, panel.right + trailing, point)
+    }
+
+    //Solves for the circulation of every panel when the local angle of attack at each
+    //control point is `angle(panel)` radians
+    fn solve<F>(&self, angle: F) -> Result<Vec<f32>, VlmError>
+        where F: Fn(&Panel) -> f32
+    {
+        let influence = self.panels.iter()
+            .map(|target| {
+                self.panels.iter()
+                    .map(|source| self.induced_velocity(source, target.control_point).z)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let rhs = self.panels.iter().map(|panel| -angle(panel)).collect();
+
+        solve_linear(influence, rhs)
+    }
+
+    //Lift of each panel for a unit free stream velocity and density
+    fn panel_lift(&self, circulation: &[f32]) -> Vec<f32>
+    {
+        self.panels.iter()
+            .zip(circulation.iter())
+            .map(|(panel, gamma)| gamma * (panel.right.y - panel.left.y))
+            .collect()
+    }
+
+    fn strip_circulation(&self, circulation: &[f32]) -> Vec<f32>
+    {
+        let mut result = vec!(0.0; self.strips.len());
+        for (panel, gamma) in self.panels.iter().zip(circulation.iter())
+        {
+            result[panel.strip] += *gamma;
+        }
+        result
+    }
+
+    //Induced drag for a unit free stream velocity and density, computed in the Trefftz plane
+    fn induced_drag(&self, circulation: &[f32]) -> f32
+    {
+        let strip_gamma = self.strip_circulation(circulation);
+
+        let mut drag = 0.0;
+        for (strip, gamma) in self.strips.iter().zip(strip_gamma.iter())
+        {
+            let y = strip.center();
+
+            let mut downwash = 0.0;
+            for (other, other_gamma) in self.strips.iter().zip(strip_gamma.iter())
+            {
+                let height = strip.height - other.height;
+
+                downwash += other_gamma / (2.0 * std::f32::consts::PI)
+                    * (trefftz_kernel(y - other.right, height) - trefftz_kernel(y - other.left, height));
+            }
+
+            drag -= 0.5 * gamma * downwash * strip.width();
+        }
+        drag
+    }
+}
+
+//Vertical velocity of a trailing vortex in the Trefftz plane at a point `y` to the side
+//and `z` above it, per unit circulation over 2 pi, smoothed inside the vortex core
+fn trefftz_kernel(y: f32, z: f32) -> f32
+{
+    y / (y * y + z * z + VORTEX_CORE_RADIUS * VORTEX_CORE_RADIUS)
+}
+
+//Biot-Savart law for a straight vortex segment from `start` to `end` with unit strength
+fn segment_velocity(start: na::Vector3<f32>, end: na::Vector3<f32>, point: na::Vector3<f32>) -> na::Vector3<f32>
+{
+    let r0 = end - start;
+    let r1 = point - start;
+    let r2 = point - end;
+
+    let cross = r1.cross(&r2);
+
+    //Points on the ends of the segment
+    if r1.norm() == 0.0 || r2.norm() == 0.0
+    {
+        return vec3(0.0, 0.0, 0.0);
+    }
+
+    //Scully core model. |r1 x r2| is the distance to the line times the segment length,
+    //so the core radius is scaled by the segment length as well
+    let core = (r0.norm() * VORTEX_CORE_RADIUS).powi(2);
+    let factor = r0.dot(&(r1 / r1.norm() - r2 / r2.norm()))
+        / (4.0 * std::f32::consts::PI * (cross.norm_squared() + core));
+
+    cross * factor
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum VlmError
+{
+    //The influence matrix has no inverse or contains NaN
+    SingularSystem,
+}
+
+impl fmt::Display for VlmError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match *self
+        {
+            VlmError::SingularSystem => write!(f, "The vortex lattice equations have no solution"),
+        }
+    }
+}
+
+//Gaussian elimination with partial pivoting
+fn solve_linear(mut matrix: Vec<Vec<f32>>, mut rhs: Vec<f32>) -> Result<Vec<f32>, VlmError>
+{
+    let size = rhs.len();
+
+    let all_finite = matrix.iter().all(|row| row.iter().all(|value| value.is_finite()))
+        && rhs.iter().all(|value| value.is_finite());
+    if !all_finite
+    {
+        return Err(VlmError::SingularSystem);
+    }
+
+    for column in 0..size
+    {
+        //Everything is finite so the comparison can't fail
+        let pivot = (column..size)
+            .max_by(|a, b| matrix[*a][column].abs().partial_cmp(&matrix[*b][column].abs()).unwrap())
+            .unwrap();
+
+        if matrix[pivot][column].abs() < SINGULAR_PIVOT
+        {
+            return Err(VlmError::SingularSystem);
+        }
+
+        matrix.swap(column, pivot);
+        rhs.swap(column, pivot);
+
+        for row in column + 1..size
+        {
+            let factor = matrix[row][column] / matrix[column][column];
+            for k in column..size
+            {
+                matrix[row][k] -= factor * matrix[column][k];
+            }
+            rhs[row] -= factor * rhs[column];
+        }
+    }
+
+    let mut result = vec!(0.0; size);
+    for row in (0..size).rev()
+    {
+        let mut sum = rhs[row];
+        for k in row + 1..size
+        {
+            sum -= matrix[row][k] * result[k];
+        }
+        result[row] = sum / matrix[row][row];
+    }
+    Ok(result)
+}
+
+//The state of the aircraft when trimmed around the CG
+pub struct TrimState
+{
+    //Angle of attack in degrees
+    pub angle: f32,
+    pub lift: f32,
+    pub induced_drag: f32,
+    //Oswald span efficiency of the wing and tail together, `None` without induced drag
+    pub span_efficiency: Option<f32>,
+
+    //Local lift coefficient along the wingspan, as (y, cl) pairs
+    pub lift_distribution: Vec<(f32, f32)>,
+}
+
+pub struct VlmAnalysis
+{
+    //Lift curve slope of the whole aircraft per radian, referenced to the wing area
+    pub lift_slope: f32,
+    //Lift coefficient at zero angle of attack caused by the incidences
+    pub zero_angle_lift: f32,
+
+    //Position of the neutral point measured from the nose
+    pub neutral_point: f32,
+
+    //`None` if the layout is unstable around the CG so there is no trim
+    pub trim: Option<TrimState>,
+}
+
+pub fn analyse(layout: &AirframeLayout, settings: &VlmSettings, cg: f32) -> Result<VlmAnalysis, VlmError>
+{
+    let lattice = Lattice::new(layout, settings);
+    let wing_area = layout.wing().area();
+
+    //The solution is linear in the angle of attack so it can be split into the part caused
+    //by the incidences and the part caused by the angle of attack
+    let gamma_zero = lattice.solve(|panel| panel.incidence)?;
+    let gamma_alpha = lattice.solve(|_| 1.0)?;
+
+    let lift_zero = lattice.panel_lift(&gamma_zero);
+    let lift_alpha = lattice.panel_lift(&gamma_alpha);
+
+    let total = |lift: &[f32]| lift.iter().sum::<f32>();
+    let moment = |lift: &[f32]| {
+        lattice.panels.iter()
+            .zip(lift.iter())
+            .map(|(panel, lift)| lift * (cg - panel.left.x))
+            .sum::<f32>()
+    };
+
+    //Lift coefficient for unit velocity and density
+    let lift_coefficient = |lift: &[f32]| 2.0 * total(lift) / wing_area;
+
+    let lift_slope = lift_coefficient(&lift_alpha);
+    let zero_angle_lift = lift_coefficient(&lift_zero);
+
+    let neutral_point = lattice.panels.iter()
+        .zip(lift_alpha.iter())
+        .map(|(panel, lift)| lift * panel.left.x)
+        .sum::<f32>() / total(&lift_alpha);
+
+    //Pitching moment around the CG is m0 + alpha * m_alpha
+    let moment_zero = moment(&lift_zero);
+    let moment_alpha = moment(&lift_alpha);
+
+    let trim = if moment_alpha < 0.0
+    {
+        let alpha = -moment_zero / moment_alpha;
+        let gamma_trim = gamma_zero.iter()
+            .zip(gamma_alpha.iter())
+            .map(|(zero, slope)| zero + alpha * slope)
+            .collect::<Vec<_>>();
+
+        let lift = lift_coefficient(&lattice.panel_lift(&gamma_trim));
+        let induced_drag = 2.0 * lattice.induced_drag(&gamma_trim) / wing_area;
+
+        let aspect_ratio = layout.wing().aspect_ratio();
+        let span_efficiency = if induced_drag > 0.0
+        {
+            Some(lift * lift / (std::f32::consts::PI * aspect_ratio * induced_drag))
+        }
+        else
+        {
+            None
+        };
+
+        let lift_distribution = lattice.strips.iter()
+            .zip(lattice.strip_circulation(&gamma_trim).iter())
+            .filter(|&(strip, _)| strip.is_wing)
+            .map(|(strip, gamma)| (strip.center(), 2.0 * gamma / strip.chord))
+            .collect();
+
+        Some(TrimState {
+            angle: alpha.to_degrees(),
+            lift,
+            induced_drag,
+            span_efficiency,
+            lift_distribution,
+        })
+    }
+    else
+    {
+        None
+    };
+
+    Ok(VlmAnalysis {
+        lift_slope,
+        zero_angle_lift,
+        neutral_point,
+        trim,
+    })
+}
+
+impl fmt::Display for VlmAnalysis
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        writeln!(f, "Lift curve slope: {:.3} /rad", self.lift_slope)?;
+        writeln!(f, "Lift coefficient at zero alpha: {:.3}", self.zero_angle_lift)?;
+        writeln!(f, "Neutral point (VLM): {:.1} mm", self.neutral_point)?;
+
+        let trim = match self.trim
+        {
+            Some(ref trim) => trim,
+            None => return writeln!(f, "Trim failed: the layout is unstable around the CG"),
+        };
+
+        writeln!(f, "Trim angle: {:.2} deg", trim.angle)?;
+        writeln!(f, "Trim lift coefficient: {:.3}", trim.lift)?;
+        writeln!(f, "Trim induced drag coefficient: {:.4}", trim.induced_drag)?;
+        match trim.span_efficiency
+        {
+            Some(efficiency) => writeln!(f, "Span efficiency: {:.3}", efficiency)?,
+            None => writeln!(f, "Span efficiency: undefined without induced drag")?,
+        }
+
+        writeln!(f, "Spanwise lift distribution:")?;
+        for &(y, cl) in &trim.lift_distribution
+        {
+            writeln!(f, "  {:7.1} mm: {:.3}", y, cl)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+use scad_generator::*;
+
+use DropPod;
+
+//Points along the edge of elliptical fins
+const ELLIPSE_POINTS: usize = 24;
+
+//Area of an outline, positive when it goes counterclockwise
+fn signed_area(outline: &[(f32, f32)]) -> f32
+{
+    (0..outline.len())
+        .map(|i| {
+            let (a, b) = (outline[i], outline[(i + 1) % outline.len()]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum::<f32>() / 2.
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum FinPlanform
+{
+    //Straight leading and trailing edges with the tip `sweep` behind the root
+    Trapezoidal,
+    //Like the trapezoid but with a trailing edge square to the pod, the sweep follows
+    //from the chords
+    ClippedDelta,
+    //Half an ellipse with its middle moved back by `sweep` at the tip
+    Elliptical,
+}
+
+//A tail section with fins that screws into the top of a mid section. Like the other
+//sections the bottom is at z = 0 and the pod flies towards -z, so the fins end flush
+//with the top of the section.
+pub struct FinCan
+{
+    pub fin_count: usize,
+    pub planform: FinPlanform,
+
+    pub root_chord: f32,
+    pub tip_chord: f32,
+    pub span: f32,
+    pub sweep: f32,
+    pub thickness: f32,
+
+    pub length: f32,
+    pub wall: f32,
+}
+
+impl FinCan
+{
+    //Outline of a fin as (distance from the root, distance from the leading edge of the
+    //root) going around the fin
+    fn outline(&self) -> Vec<(f32, f32)>
+    {
+        match self.planform
+        {
+            FinPlanform::Trapezoidal => vec!(
+                (0., 0.),
+                (self.span, self.sweep),
+                (self.span, self.sweep + self.tip_chord),
+                (0., self.root_chord),
+            ),
+            FinPlanform::ClippedDelta => vec!(
+                (0., 0.),
+                (self.span, self.root_chord - self.tip_chord),
+                (self.span, self.root_chord),
+                (0., self.root_chord),
+            ),
+            FinPlanform::Elliptical => {
+                //From the trailing edge of the root over the tip to the leading edge
+                (0..ELLIPSE_POINTS + 1)
+                    .map(|i| {
+                        let angle = i as f32 / ELLIPSE_POINTS as f32 * std::f32::consts::PI;
+                        (
+                            self.span * angle.sin(),
+                            self.root_chord / 2. * (1. + angle.cos()) + self.sweep * angle.sin()
+                        )
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    //Area of one fin
+    pub fn fin_area(&self) -> f32
+    {
+        signed_area(&self.outline()).abs()
+    }
+
+    //Volume of plastic in mm^3 of the fin can with its fins
+    pub fn material_volume(&self, pod: &DropPod) -> f32
+    {
+        let inner_radius = pod.outer_radius - self.wall;
+        let tube = std::f32::consts::PI * (pod.outer_radius.powi(2) - inner_radius.powi(2)) * self.length;
+        let fins = self.fin_area() * self.thickness * self.fin_count as f32;
+
+        tube + fins + pod.plug_volume()
+    }
+
+    //The fin outline made `thickness` thick, with the root along z at x = 0
+    fn fin(&self) -> ScadObject
+    {
+        let mut outline = self.outline();
+
+        //The faces below are ordered for a counterclockwise outline
+        if signed_area(&outline) < 0.
+        {
+            outline.reverse();
+        }
+
+        let count = outline.len();
+        let mut points = vec!();
+        for y in &[self.thickness / 2., -self.thickness / 2.]
+        {
+            for &(r, z) in &outline
+            {
+                points.push(vec3(r, *y, z));
+            }
+        }
+
+        let index = |side: usize, point: usize| (side * count + point % count) as i32;
+
+        //Faces are listed clockwise when seen from the outside
+        let mut faces = vec!();
+        faces.push((0..count).map(|i| index(0, i)).collect());
+        faces.push((0..count).rev().map(|i| index(1, i)).collect());
+        for i in 0..count
+        {
+            faces.push(vec!(index(0, i), index(1, i), index(1, i + 1), index(0, i + 1)));
+        }
+
+        scad!(Polyhedron(points, faces))
+    }
+
+    pub fn get(&self, pod: &DropPod) -> ScadObject
+    {
+        let inner_radius = pod.outer_radius - self.wall;
+
+        let tube = scad!(Difference;
+        {
+            scad!(Cylinder(self.length, Radius(pod.outer_radius))),
+            scad!(Translate(vec3(0., 0., -1.));
+            {
+                scad!(Cylinder(self.length + 2., Radius(inner_radius)))
+            })
+        });
+
+        let thread = scad!(Translate(vec3(0., 0., -pod.screw_height));
+        {
+            pod.get_joint(false)
+        });
+
+        let mut fins = scad!(Union);
+        for i in 0..self.fin_count
+        {
+            //The root goes half way into the wall
+            fins.add_child(scad!(Rotate(360. / self.fin_count as f32 * i as f32, vec3(0., 0., 1.));
+            {
+                scad!(Translate(vec3(pod.outer_radius - self.wall / 2., 0., self.length - self.root_chord));
+                {
+                    self.fin()
+                })
+            }));
+        }
+
+        scad!(Union;{tube, thread, fins})
+    }
+}
@@ -0,0 +1,147 @@
+use scad_generator::*;
+
+//Largest angle between the steps of the sectors, keeps the outer edge close to round
+const SECTOR_STEP: f32 = 10.;
+
+//How the sections of the pod are held together
+#[derive(Clone, Copy, Debug)]
+pub enum JointType
+{
+    Screw,
+    Bayonet(Bayonet),
+}
+
+//A quarter turn lock. The male side has lugs around the middle that go down slots in the
+//female side and are then turned into a groove, past a bump that stops them backing out.
+#[derive(Clone, Copy, Debug)]
+pub struct Bayonet
+{
+    pub lugs: usize,
+    //Width of each lug in degrees
+    pub lug_angle: f32,
+    //How far the joint is turned to lock it in degrees
+    pub turn: f32,
+    //Radius of the detent bump
+    pub detent: f32,
+}
+
+//Part of a ring between two angles in degrees
+fn sector(inner_radius: f32, outer_radius: f32, z: f32, height: f32, from: f32, to: f32) -> ScadObject
+{
+    let steps = ((to - from) / SECTOR_STEP).ceil().max(1.) as usize;
+
+    let spoke = |angle: f32| scad!(Rotate(angle, vec3(0., 0., 1.));
+    {
+        scad!(Translate(vec3(inner_radius, -0.005, z));
+        {
+            scad!(Cube(vec3(outer_radius - inner_radius, 0.01, height)))
+        })
+    });
+
+    let mut result = scad!(Union);
+    for i in 0..steps
+    {
+        let start = from + (to - from) * i as f32 / steps as f32;
+        let end = from + (to - from) * (i + 1) as f32 / steps as f32;
+
+        result.add_child(scad!(Hull;{spoke(start), spoke(end)}));
+    }
+    result
+}
+
+impl Bayonet
+{
+    //Half the angle of a slot in the female side, the lug with `clearance` on both sides
+    fn half_width(&self, radius: f32, clearance: f32) -> f32
+    {
+        self.lug_angle / 2. + clearance.to_degrees() / radius
+    }
+
+    //The groove behind each slot has to end before the slot of the next lug
+    fn check(&self, radius: f32, clearance: f32)
+    {
+        if self.turn + 2. * self.half_width(radius, clearance) >= 360. / self.lugs as f32
+        {
+            panic!("The bayonet lugs are too wide to turn {} degrees", self.turn);
+        }
+    }
+
+    fn lug_angles(&self) -> Vec<f32>
+    {
+        (0..self.lugs).map(|i| 360. / self.lugs as f32 * i as f32).collect()
+    }
+
+    fn lug_height(&self, height: f32) -> f32
+    {
+        height / 2.
+    }
+
+    //Core with lugs `depth` deep in the middle of its height. The lugs are centered so
+    //the joint works whichever end of it faces the section
+    pub fn male(&self, radius: f32, depth: f32, height: f32) -> ScadObject
+    {
+        let lug_height = self.lug_height(height);
+        let mut result = scad!(Union;{scad!(Cylinder(height, Radius(radius)))});
+
+        for angle in self.lug_angles()
+        {
+            result.add_child(sector(
+                radius - 0.5,
+                radius + depth,
+                (height - lug_height) / 2.,
+                lug_height,
+                angle - self.lug_angle / 2.,
+                angle + self.lug_angle / 2.
+            ));
+        }
+        result
+    }
+
+    //The hole for the male side made with `radius`, `depth` and `height`. The slots go
+    //through the whole joint so either end can be the opening. The lugs are checked here
+    //since the clearance decides how much room is left to turn them
+    pub fn female(&self, radius: f32, depth: f32, clearance: f32, height: f32) -> ScadObject
+    {
+        self.check(radius, clearance);
+
+        let lug_height = self.lug_height(height);
+        let outer_radius = radius + depth + clearance;
+        let half_width = self.half_width(radius, clearance);
+
+        let groove_bottom = (height - lug_height) / 2. - clearance;
+
+        let mut result = scad!(Union;{scad!(Cylinder(height, Radius(radius + clearance)))});
+
+        for angle in self.lug_angles()
+        {
+            let slot = sector(radius - 0.5, outer_radius, 0., height, angle - half_width, angle + half_width);
+
+            let groove = sector(
+                radius - 0.5,
+                outer_radius,
+                groove_bottom,
+                lug_height + clearance * 2.,
+                angle - half_width,
+                angle + self.turn + half_width
+            );
+
+            //Left standing on the bottom of the groove just behind the locked lug. It is
+            //centered on the path of the bottom of the lugs rather than the groove so the
+            //lugs have to ride over the whole `detent` and not just what the clearance leaves
+            let detent_angle = (angle + self.turn - half_width - self.detent.to_degrees() / radius).to_radians();
+            let detent_radius = radius + depth / 2.;
+            let detent = scad!(Translate(vec3(
+                        detent_radius * detent_angle.cos(),
+                        detent_radius * detent_angle.sin(),
+                        groove_bottom + clearance
+                    ));
+            {
+                scad!(Sphere(Radius(self.detent)))
+            });
+
+            result.add_child(slot);
+            result.add_child(scad!(Difference;{groove, detent}));
+        }
+        result
+    }
+}
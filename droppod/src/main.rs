@@ -5,35 +5,170 @@
 extern crate scad_generator;
 extern crate nalgebra as na;
 extern crate scad_util as su;
+extern crate rc_common;
 
 use std::vec::Vec;
 
 //Avoid having to write scad_generator:: everywhere
 use scad_generator::*;
 
+mod release;
+mod parachute;
+mod fin_can;
+mod trajectory;
+mod stack;
+mod joint;
+mod sled;
+
+use release::ReleaseLatch;
+use parachute::{CanopyShape, DoorRelease, Parachute, ParachuteBay};
+use fin_can::{FinCan, FinPlanform};
+use trajectory::{DropSimulation, ParachuteDeployment};
+use stack::{PodStack, Tail};
+use joint::{Bayonet, JointType};
+use sled::{Board, ElectronicsSled, SledKey};
+use rc_common::servo::ServoSpec;
+
+//Which way the thread at the end of a section goes
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Joint
+{
+    Male,
+    Female,
+}
+
+#[derive(Clone, Copy)]
 struct DropPod 
 {
     pub outer_radius: f32,
+    //Length of the joint between sections
     pub screw_height: f32,
+    pub joint_type: JointType,
+    //Groove along the inside of the mid sections for an electronics sled
+    pub sled_key: Option<SledKey>,
 }
 
+//How far the threads or bayonet lugs stick out of the core of the joint
+const THREAD_WIDTH: f32 = 1.5;
+//Gap between the male and female side of a joint
+const JOINT_CLEARANCE: f32 = 0.4;
+
+//Density of PLA in g/mm^3
+const PLA_DENSITY: f32 = 0.00124;
+
 impl DropPod
 {
-    pub fn get_mid_section(&self, height: f32) -> ScadObject 
+    //Inner radius of the mid sections
+    pub fn inner_radius(&self) -> f32
+    {
+        self.outer_radius - 5.
+    }
+
+    //Drag coefficient times frontal area in mm^2 when flying behind a nose cone
+    //`nose_height` long
+    pub fn drag_area(&self, nose_height: f32) -> f32
+    {
+        let diameter = self.outer_radius * 2.;
+        let frontal_area = std::f32::consts::PI * self.outer_radius.powi(2);
+
+        trajectory::drag_coefficient(nose_height, diameter) * frontal_area
+    }
+
+    //Volume of plastic in mm^3 of a solid male thread, ignoring the thread itself
+    pub fn plug_volume(&self) -> f32
+    {
+        std::f32::consts::PI * self.screw_radius(false).powi(2) * self.screw_height
+    }
+
+    //Volume of plastic in mm^3 of a nose cone `height` long
+    pub fn nose_cone_volume(&self, height: f32) -> f32
+    {
+        //The radius grows with the square root of the length, which makes it a paraboloid
+        std::f32::consts::PI * self.outer_radius.powi(2) * height / 2. + self.plug_volume()
+    }
+
+    //Volume of plastic in mm^3 of a section from `get_section`
+    pub fn section_volume(&self, height: f32, bottom: Joint, top: Joint) -> f32
+    {
+        let inner_radius = self.inner_radius();
+        let wall_area = std::f32::consts::PI * (self.outer_radius.powi(2) - inner_radius.powi(2));
+        let male_area = std::f32::consts::PI * (self.screw_radius(false).powi(2) - self.bore_radius().powi(2));
+
+        let male_ends = [bottom, top].iter().filter(|joint| **joint == Joint::Male).count();
+
+        wall_area * height + male_area * self.screw_height * male_ends as f32
+    }
+
+    //Room for the payload in mm^3 inside a section from `get_section`. The male threads
+    //of the neighbouring sections take up the space behind female threads
+    pub fn section_payload_volume(&self, height: f32, bottom: Joint, top: Joint) -> f32
+    {
+        let inner_radius = self.inner_radius();
+        let inner_area = std::f32::consts::PI * inner_radius.powi(2);
+        let bore_area = std::f32::consts::PI * self.bore_radius().powi(2);
+
+        [bottom, top].iter()
+            .map(|joint| match *joint
+            {
+                Joint::Male => bore_area * self.screw_height,
+                Joint::Female => -inner_area * self.screw_height,
+            })
+            .sum::<f32>() + inner_area * height
+    }
+
+    //`latch` adds the catch for the release mechanism in the middle of the section
+    pub fn get_mid_section(&self, height: f32, latch: Option<&ReleaseLatch>) -> ScadObject 
+    {
+        self.get_section(height, Joint::Female, Joint::Female, latch)
+    }
+
+    //A mid section with the given thread at each end. Male threads stick out of the
+    //section and are hollow so the payload space goes through them
+    pub fn get_section(&self, height: f32, bottom: Joint, top: Joint, latch: Option<&ReleaseLatch>) -> ScadObject 
     {
-        let inner_radius = self.outer_radius - 5.;
+        let inner_radius = self.inner_radius();
 
         let outer_cylinder = scad!(Cylinder(height, Radius(self.outer_radius)));
         let inner_cylinder = scad!(Cylinder(height, Radius(inner_radius)));
 
-        let bottom_screw = self.get_screw(true);
+        let outer_cylinder = match latch
+        {
+            Some(latch) => scad!(Union;{outer_cylinder, latch.catch(self.outer_radius, height / 2.)}),
+            None => outer_cylinder,
+        };
+
+        let mut body = scad!(Union;{outer_cylinder});
+        let mut cutouts = scad!(Union;{inner_cylinder});
+
+        match bottom
+        {
+            Joint::Male => body.add_child(scad!(Translate(vec3(0., 0., -self.screw_height));{self.get_joint(false)})),
+            Joint::Female => cutouts.add_child(self.get_joint(true)),
+        }
 
-        let top_screw = scad!(Translate(vec3(0., 0., height - self.screw_height));{self.get_screw(true)});
+        match top
+        {
+            Joint::Male => body.add_child(scad!(Translate(vec3(0., 0., height));{self.get_joint(false)})),
+            Joint::Female => cutouts.add_child(scad!(Translate(vec3(0., 0., height - self.screw_height));{self.get_joint(true)})),
+        }
 
-        scad!(Difference;{outer_cylinder, inner_cylinder, bottom_screw, top_screw})
+        if let Some(key) = self.sled_key
+        {
+            cutouts.add_child(key.groove(inner_radius, height));
+        }
+
+        //Hollow out the male threads
+        let bore = scad!(Translate(vec3(0., 0., -self.screw_height - 1.));
+        {
+            scad!(Cylinder(height + self.screw_height * 2. + 2., Radius(self.bore_radius())))
+        });
+        cutouts.add_child(bore);
+
+        scad!(Difference;{body, cutouts})
     }
 
-    pub fn get_nose_cone(&self, height: f32) -> ScadObject
+    //The cone from its tip at z = 0 to the full radius at `height`
+    fn get_cone(&self, height: f32) -> ScadObject
     {
         let ring_amount = 10;
         let ring_radii = 
@@ -68,25 +203,82 @@ impl DropPod
             cone.add_child(section);
         }
         
+        cone
+    }
+
+    pub fn get_nose_cone(&self, height: f32) -> ScadObject
+    {
         scad!(Union;{
-            cone,
-            scad!(Translate(vec3(0., 0., height));{self.get_screw(false)}),
+            self.get_cone(height),
+            scad!(Translate(vec3(0., 0., height));{self.get_joint(false)}),
         })
     }
 
-    fn get_screw(&self, is_outer: bool) -> ScadObject 
+    //A nose cone turned around to close the back of the pod, with the joint below z = 0.
+    //Only the cone is mirrored so the thread keeps its direction
+    pub fn get_tail_cone(&self, height: f32) -> ScadObject
+    {
+        scad!(Union;{
+            scad!(Translate(vec3(0., 0., height));
+            {
+                scad!(Mirror(vec3(0., 0., 1.));{self.get_cone(height)})
+            }),
+            scad!(Translate(vec3(0., 0., -self.screw_height));{self.get_joint(false)}),
+        })
+    }
+
+    //Radius of the core of the joint, the female one is bigger to leave some room
+    fn screw_radius(&self, is_outer: bool) -> f32
     {
-        let height = self.screw_height;
         let outside_padding = 4.;
-        let thread_width = 1.5;
-        let thread_height = 3.;
 
-        let mut inner_radius = self.outer_radius - thread_width - outside_padding + 0.01;
+        let inner_radius = self.outer_radius - THREAD_WIDTH - outside_padding + 0.01;
 
         if is_outer
         {
-            inner_radius = inner_radius + 0.4;
+            inner_radius + JOINT_CLEARANCE
+        }
+        else
+        {
+            inner_radius
         }
+    }
+
+    //Radius of the hole through male threads on mid sections
+    fn bore_radius(&self) -> f32
+    {
+        self.screw_radius(false) - 2.
+    }
+
+    //The male end of a section or the cut for the female one, `screw_height` long
+    fn get_joint(&self, is_outer: bool) -> ScadObject
+    {
+        match self.joint_type
+        {
+            JointType::Screw => self.get_screw(is_outer),
+            JointType::Bayonet(ref bayonet) =>
+            {
+                let radius = self.screw_radius(false);
+
+                if is_outer
+                {
+                    bayonet.female(radius, THREAD_WIDTH, JOINT_CLEARANCE, self.screw_height)
+                }
+                else
+                {
+                    bayonet.male(radius, THREAD_WIDTH, self.screw_height)
+                }
+            }
+        }
+    }
+
+    fn get_screw(&self, is_outer: bool) -> ScadObject 
+    {
+        let height = self.screw_height;
+        let thread_width = THREAD_WIDTH;
+        let thread_height = 3.;
+
+        let inner_radius = self.screw_radius(is_outer);
 
         let center_piece = scad!(Cylinder(height, Radius(inner_radius)));
         let threads = su::threads::thread(height, inner_radius, thread_width, thread_height);
@@ -99,19 +291,28 @@ impl DropPod
 }
 
 
+//Height of the plate between the pod and the plane
+const MID_SECTION_HEIGHT: f32 = 40.;
+
 struct MountPoint 
 {
     pub pod_radius: f32,
     pub length: f32,
+    pub latch: ReleaseLatch,
 }
 
 impl MountPoint
 {
     pub fn get(&self) -> ScadObject 
     {
-        scad!(Union;{
-            self.pod_mount(),
-            self.mid_section(),
+        scad!(Difference;{
+            scad!(Union;{
+                self.pod_mount(),
+                self.mid_section(),
+                self.hole_beam(),
+                self.latch.housing(self.length),
+            }),
+            self.latch.cutout(self.length),
         })
     }
 
@@ -144,7 +345,7 @@ impl MountPoint
     fn mid_section(&self) -> ScadObject
     {
         let thickness = 5.;
-        let height = 40.;
+        let height = MID_SECTION_HEIGHT;
 
         let main_cube = scad!(Translate(vec3(-thickness / 2., 0., 0.));
         {
@@ -154,10 +355,33 @@ impl MountPoint
         main_cube
     }
 
+    //Beam along the top of the mid section with holes for bolting it to the plane
     fn hole_beam(&self) -> ScadObject 
     {
         let hole_radius = 3.5;
         let hole_padding = 2.;
+
+        let width = (hole_radius + hole_padding) * 2.;
+        let thickness = hole_padding * 2.;
+
+        let beam = scad!(Translate(vec3(-width / 2., MID_SECTION_HEIGHT - thickness, 0.));
+        {
+            scad!(Cube(vec3(width, thickness, self.length)))
+        });
+
+        let mut holes = scad!(Union);
+        for z in &[self.length / 4., self.length * 3. / 4.]
+        {
+            holes.add_child(scad!(Translate(vec3(0., MID_SECTION_HEIGHT - thickness - 1., *z));
+            {
+                scad!(Rotate(-90., vec3(1., 0., 0.));
+                {
+                    scad!(Cylinder(thickness + 2., Radius(hole_radius)))
+                })
+            }));
+        }
+
+        scad!(Difference;{beam, holes})
     }
 }
 
@@ -172,16 +396,143 @@ pub fn main()
     {
         outer_radius: 20.,
         screw_height: 8.,
+        joint_type: JointType::Screw,
+        sled_key: None,
+    };
+
+    let latch = ReleaseLatch
+    {
+        servo: ServoSpec::micro_9g(),
+        arm_length: 12.,
+        pin_diameter: 2.,
+        pin_engagement: 3.,
+        lug_width: 4.,
+        lug_height: 8.,
+        lug_length: 8.,
+        lug_root: 3.,
+        clearance: 0.3,
+        wall: 2.,
     };
 
+    println!(
+        "Release pin travel: {:.1} mm needed, {:.1} mm available",
+        latch.required_travel(),
+        latch.available_travel()
+    );
+
     let mount = MountPoint
     {
         pod_radius: 20.,
-        length: 40.
+        length: 40.,
+        latch,
+    };
+
+    //A pod with a nose cone, a 80 mm mid section and the parachute bay carrying 100 g
+    let mut chute_pod = PodStack
+    {
+        name: String::from("chute"),
+        pod,
+        nose_height: 40.,
+        mid_heights: vec!(80.),
+        parachute_bay: None,
+        tail: None,
+        latch: Some(mount.latch),
+    };
+
+    let payload = 100.;
+    let bay_for = |parachute: &Parachute| ParachuteBay
+    {
+        packed_volume: parachute.packed_volume(),
+        release: DoorRelease::Spring {spring_diameter: 15., seat_height: 5., pin_diameter: 2.},
+        door_thickness: 2.,
+        lip_depth: 5.,
+        clearance: 0.3,
+        eye_size: 6.,
+        eye_hole: 3.,
+    };
+
+    //The size of the bay depends on the parachute, so the parachute is first sized
+    //without the bay and then again with the bay it needs
+    let parachute = Parachute::for_descent_rate(CanopyShape::Round, chute_pod.empty_mass() + payload, 4.);
+    chute_pod.parachute_bay = Some(bay_for(&parachute));
+
+    let parachute = Parachute::for_descent_rate(CanopyShape::Round, chute_pod.empty_mass() + payload, 4.);
+    chute_pod.parachute_bay = Some(bay_for(&parachute));
+    print!("{}", parachute);
+    print!("{}", chute_pod);
+
+    let mut drop = DropSimulation
+    {
+        mass: chute_pod.empty_mass() + payload,
+        drag_area: pod.drag_area(chute_pod.nose_height),
+        parachute: None,
+        altitude: 50.,
+        airspeed: 12.,
+        wind: na::Vector2::new(-3., 2.),
     };
+    println!("Free fall from {} m:", drop.altitude);
+    print!("{}", drop.simulate());
+
+    drop.parachute = Some(ParachuteDeployment {drag_area: parachute.drag_area(), deploy_delay: 1.});
+    println!("With the parachute:");
+    print!("{}", drop.simulate());
+
+    chute_pod.write_sections(50);
+
+    //A longer pod with fins and no parachute
+    let fin_pod = PodStack
+    {
+        name: String::from("fin"),
+        pod: DropPod
+        {
+            joint_type: JointType::Bayonet(Bayonet
+            {
+                lugs: 3,
+                lug_angle: 30.,
+                turn: 45.,
+                detent: 0.5,
+            }),
+            sled_key: Some(SledKey {width: 3., depth: 1.5, clearance: 0.2}),
+            ..pod
+        },
+        nose_height: 40.,
+        mid_heights: vec!(80., 40.),
+        parachute_bay: None,
+        tail: Some(Tail::FinCan(FinCan
+        {
+            fin_count: 4,
+            planform: FinPlanform::ClippedDelta,
+            root_chord: 50.,
+            tip_chord: 25.,
+            span: 30.,
+            sweep: 25.,
+            thickness: 2.,
+            length: 60.,
+            wall: 2.,
+        })),
+        latch: Some(mount.latch),
+    };
+    print!("{}", fin_pod);
+
+    fin_pod.write_sections(50);
+
+    //A logger in the first mid section of the fin pod
+    let sled = ElectronicsSled
+    {
+        length: 60.,
+        deck_offset: 8.,
+        thickness: 1.5,
+        clearance: 0.3,
+        boards: vec!((Board::by_name("raspberry_pi_pico").unwrap(), 4.5)),
+        standoff_height: 3.,
+        standoff_wall: 1.2,
+    };
+
+    let mut sled_file = ScadFile::new();
+    sled_file.set_detail(50);
+    sled_file.add_object(sled.get(&fin_pod.pod));
+    sled_file.write_to_file(String::from("fin_sled_auto.scad"));
 
-    //sfile.add_object(pod.get_nose_cone(40.));
-    //sfile.add_object(pod.get_mid_section(80.));
     sfile.add_object(mount.get());
 
     sfile.write_to_file(String::from("cargo_auto.scad"));
@@ -0,0 +1,282 @@
+use std::fmt;
+
+use scad_generator::*;
+
+use DropPod;
+
+//Air density at sea level in kg/m^3
+pub const AIR_DENSITY: f32 = 1.225;
+pub const GRAVITY: f32 = 9.81;
+
+//Thickness of the canopy fabric and how much room it takes packed compared to laid flat
+const FABRIC_THICKNESS: f32 = 0.07;
+const PACKING_FACTOR: f32 = 2.5;
+
+#[derive(Clone, Copy, Debug)]
+pub enum CanopyShape
+{
+    Round,
+    //Two rectangles crossed in the middle, each three arms long and one arm wide
+    Cross,
+}
+
+impl CanopyShape
+{
+    fn drag_coefficient(&self) -> f32
+    {
+        match *self
+        {
+            CanopyShape::Round => 0.75,
+            CanopyShape::Cross => 0.6,
+        }
+    }
+}
+
+pub struct Parachute
+{
+    pub shape: CanopyShape,
+    //Canopy area in mm^2
+    pub area: f32,
+}
+
+impl Parachute
+{
+    //The canopy needed to bring `mass` grams down at `descent_rate` m/s
+    pub fn for_descent_rate(shape: CanopyShape, mass: f32, descent_rate: f32) -> Parachute
+    {
+        let weight = mass / 1000.0 * GRAVITY;
+        let area = 2.0 * weight / (AIR_DENSITY * shape.drag_coefficient() * descent_rate * descent_rate);
+
+        Parachute {
+            shape,
+            area: area * 1_000_000.0,
+        }
+    }
+
+    //Diameter of a round canopy or the width of one arm of a cross canopy
+    pub fn size(&self) -> f32
+    {
+        match self.shape
+        {
+            CanopyShape::Round => (4.0 * self.area / std::f32::consts::PI).sqrt(),
+            CanopyShape::Cross => (self.area / 5.0).sqrt(),
+        }
+    }
+
+    pub fn drag_area(&self) -> f32
+    {
+        self.area * self.shape.drag_coefficient()
+    }
+
+    //Rough volume of the packed canopy in mm^3, without the shroud lines
+    pub fn packed_volume(&self) -> f32
+    {
+        self.area * FABRIC_THICKNESS * PACKING_FACTOR
+    }
+}
+
+impl fmt::Display for Parachute
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self.shape
+        {
+            CanopyShape::Round => writeln!(f, "Round parachute: {:.0} mm diameter", self.size())?,
+            CanopyShape::Cross => writeln!(
+                f,
+                "Cross parachute: {:.0} mm arms, {:.0} mm across",
+                self.size(),
+                self.size() * 3.0
+            )?,
+        }
+        writeln!(f, "Packed volume: {:.1} cm^3", self.packed_volume() / 1000.0)
+    }
+}
+
+//How the door at the back of the bay is opened
+#[derive(Clone, Copy, Debug)]
+pub enum DoorRelease
+{
+    //A compression spring on the bottom of the bay pushes the door off. The door is held
+    //shut by a pin going down through the top of the bay wall and the lip of the door.
+    //The pin is on a lanyard tied to the mount, so it is pulled out when the pod drops
+    Spring
+    {
+        spring_diameter: f32,
+        seat_height: f32,
+        pin_diameter: f32,
+    },
+    //A line from the door to the plane pulls it off when the pod drops
+    Drag,
+}
+
+//A section with a parachute inside and a door at the end. The bottom of the section is
+//a solid plug with a male thread that screws into the top of a mid section.
+pub struct ParachuteBay
+{
+    //Volume of the packed parachute in mm^3
+    pub packed_volume: f32,
+    pub release: DoorRelease,
+
+    pub door_thickness: f32,
+    //How far the lip of the door goes into the bay
+    pub lip_depth: f32,
+    pub clearance: f32,
+
+    //Loops for the shroud lines and the door tether
+    pub eye_size: f32,
+    pub eye_hole: f32,
+}
+
+//Wall around the spring
+const SEAT_WALL: f32 = 2.;
+
+impl ParachuteBay
+{
+    //Space from the bulkhead to the lip of the door
+    pub fn inner_length(&self, pod: &DropPod) -> f32
+    {
+        let seat = match self.release
+        {
+            DoorRelease::Spring {seat_height, ..} => seat_height,
+            DoorRelease::Drag => 0.,
+        };
+
+        let area = std::f32::consts::PI * pod.inner_radius().powi(2);
+        self.packed_volume / area + seat + self.eye_size
+    }
+
+    pub fn length(&self, pod: &DropPod) -> f32
+    {
+        self.inner_length(pod) + self.lip_depth
+    }
+
+    //Volume of plastic in mm^3 of the bay and the door, ignoring the eyes
+    pub fn material_volume(&self, pod: &DropPod) -> f32
+    {
+        let pi = std::f32::consts::PI;
+        let lip_radius = pod.inner_radius() - self.clearance;
+
+        let tube = pi * (pod.outer_radius.powi(2) - pod.inner_radius().powi(2)) * self.length(pod);
+        let door = pi * pod.outer_radius.powi(2) * self.door_thickness
+            + pi * (lip_radius.powi(2) - (lip_radius - 2.).powi(2)) * self.lip_depth;
+
+        tube + door + pod.plug_volume()
+    }
+
+    //A bridge with a hole going across it along x, standing on z = 0
+    fn eye(&self) -> ScadObject
+    {
+        let length = self.eye_size * 2.;
+
+        let block = scad!(Translate(vec3(-length / 2., -self.eye_size / 2., 0.));
+        {
+            scad!(Cube(vec3(length, self.eye_size, self.eye_size)))
+        });
+
+        let hole = scad!(Translate(vec3(-length / 2. - 1., 0., self.eye_size / 2.));
+        {
+            scad!(Rotate(90., vec3(0., 1., 0.));
+            {
+                scad!(Cylinder(length + 2., Diameter(self.eye_hole)))
+            })
+        });
+
+        scad!(Difference;{block, hole})
+    }
+
+    //Hole for the pin holding the door along y through the top of the bay wall and the
+    //lip of the door, centered on `z` along the lip
+    fn pin_hole(&self, pod: &DropPod, pin_diameter: f32, z: f32) -> ScadObject
+    {
+        if pin_diameter + self.clearance * 2. > self.lip_depth
+        {
+            panic!("The {} mm door pin does not fit in the {} mm lip of the door", pin_diameter, self.lip_depth);
+        }
+
+        //Starts inside the lip to stay clear of the eye on the door
+        let start = pod.inner_radius() - self.clearance - 3.;
+
+        scad!(Translate(vec3(0., start, z));
+        {
+            scad!(Rotate(-90., vec3(1., 0., 0.));
+            {
+                scad!(Cylinder(pod.outer_radius - start + 1., Diameter(pin_diameter + self.clearance)))
+            })
+        })
+    }
+
+    pub fn get(&self, pod: &DropPod) -> ScadObject
+    {
+        let length = self.length(pod);
+
+        let tube = scad!(Difference;
+        {
+            scad!(Cylinder(length, Radius(pod.outer_radius))),
+            scad!(Translate(vec3(0., 0., -1.));
+            {
+                scad!(Cylinder(length + 2., Radius(pod.inner_radius())))
+            })
+        });
+
+        let bulkhead = scad!(Translate(vec3(0., 0., -pod.screw_height));
+        {
+            pod.get_joint(false)
+        });
+
+        let mut result = scad!(Union;{tube, bulkhead, self.eye()});
+
+        if let DoorRelease::Spring {spring_diameter, seat_height, pin_diameter} = self.release
+        {
+            result.add_child(scad!(Difference;
+            {
+                scad!(Cylinder(seat_height, Diameter(spring_diameter + self.clearance * 2. + SEAT_WALL * 2.))),
+                scad!(Translate(vec3(0., 0., -1.));
+                {
+                    scad!(Cylinder(seat_height + 2., Diameter(spring_diameter + self.clearance * 2.)))
+                })
+            }));
+
+            result = scad!(Difference;{result, self.pin_hole(pod, pin_diameter, length - self.lip_depth / 2.)});
+        }
+        result
+    }
+
+    //The door printed with the outside face down. The tether for the drag line is on
+    //the outside, the one holding the door to the parachute on the inside
+    pub fn get_door(&self, pod: &DropPod) -> ScadObject
+    {
+        let lip_radius = pod.inner_radius() - self.clearance;
+
+        let mut door = scad!(Union;
+        {
+            scad!(Cylinder(self.door_thickness, Radius(pod.outer_radius))),
+            scad!(Translate(vec3(0., 0., self.door_thickness));
+            {
+                scad!(Difference;
+                {
+                    scad!(Cylinder(self.lip_depth, Radius(lip_radius))),
+                    scad!(Translate(vec3(0., 0., -1.));
+                    {
+                        scad!(Cylinder(self.lip_depth + 2., Radius(lip_radius - 2.)))
+                    })
+                })
+            }),
+            scad!(Translate(vec3(0., 0., self.door_thickness)); {self.eye()}),
+        });
+
+        match self.release
+        {
+            DoorRelease::Drag => door.add_child(scad!(Mirror(vec3(0., 0., 1.)); {self.eye()})),
+            //The door is turned to line the hole up with the one in the bay
+            DoorRelease::Spring {pin_diameter, ..} => {
+                door = scad!(Difference;
+                {
+                    door,
+                    self.pin_hole(pod, pin_diameter, self.door_thickness + self.lip_depth / 2.)
+                });
+            }
+        }
+        door
+    }
+}
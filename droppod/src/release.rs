@@ -0,0 +1,153 @@
+use scad_generator::*;
+
+use rc_common::servo::ServoSpec;
+
+//A latch where a servo pulls a pin out of a lug on top of the pod. The pin goes along
+//the pod through the mount and the lug and the servo sits on the side of the mount with
+//its arm at the front end of the pin.
+//
+//Coordinates are those of the mount: the pod goes along z and its top is at y = 0 in
+//the middle of the mount in x.
+#[derive(Clone, Copy)]
+pub struct ReleaseLatch
+{
+    pub servo: ServoSpec,
+    //Distance from the servo shaft to the hole the pin is hooked into
+    pub arm_length: f32,
+
+    pub pin_diameter: f32,
+    //How far the pin goes into the back wall of the housing when locked
+    pub pin_engagement: f32,
+
+    pub lug_width: f32,
+    pub lug_height: f32,
+    pub lug_length: f32,
+    //How far the lug goes into the wall of the pod
+    pub lug_root: f32,
+
+    pub clearance: f32,
+    pub wall: f32,
+}
+
+impl ReleaseLatch
+{
+    pub fn pin_height(&self) -> f32
+    {
+        self.lug_height / 2.0
+    }
+
+    //Cylinder along z at the height of the pin
+    fn pin_cylinder(&self, start: f32, length: f32, diameter: f32) -> ScadObject
+    {
+        scad!(Translate(vec3(0.0, self.pin_height(), start));
+        {
+            scad!(Cylinder(length, Diameter(diameter)))
+        })
+    }
+
+    //Lug with the pin hole, starting at `z`
+    fn lug(&self, z: f32) -> ScadObject
+    {
+        let lug = scad!(Translate(vec3(-self.lug_width / 2.0, -self.lug_root, z));
+        {
+            scad!(Cube(vec3(self.lug_width, self.lug_height + self.lug_root, self.lug_length)))
+        });
+
+        scad!(Difference;
+        {
+            lug,
+            self.pin_cylinder(z - 1.0, self.lug_length + 2.0, self.pin_diameter + self.clearance)
+        })
+    }
+
+    //The catch on the pod, centered on `z` along a pod with `outer_radius` in the
+    //coordinates of the pod
+    pub fn catch(&self, outer_radius: f32, z: f32) -> ScadObject
+    {
+        scad!(Translate(vec3(0.0, outer_radius, 0.0));
+        {
+            self.lug(z - self.lug_length / 2.0)
+        })
+    }
+
+    fn slot_length(&self) -> f32
+    {
+        self.lug_length + self.clearance * 2.0
+    }
+
+    fn housing_width(&self) -> f32
+    {
+        self.lug_width + (self.clearance + self.wall) * 2.0
+    }
+
+    //Distance the pin has to move to let go of the lug
+    pub fn required_travel(&self) -> f32
+    {
+        self.slot_length() + self.pin_engagement
+    }
+
+    //Movement of the pin when the servo goes from one end to the other
+    pub fn available_travel(&self) -> f32
+    {
+        2.0 * self.arm_length * self.servo.travel.to_radians().sin()
+    }
+
+    //Moves the servo from its own coordinates to the side of the housing with the shaft
+    //pointing sideways and the arm at the front of the mount
+    fn place_servo(&self, object: ScadObject) -> ScadObject
+    {
+        scad!(Translate(vec3(self.housing_width() / 2.0, self.wall, self.servo.shaft_offset));
+        {
+            scad!(Rotate(90.0, vec3(0.0, 1.0, 0.0));
+            {
+                object
+            })
+        })
+    }
+
+    //The housing around the lug and the servo frame. `length` is the length of the mount
+    //and the lug is in the middle of it
+    pub fn housing(&self, length: f32) -> ScadObject
+    {
+        let height = self.lug_height + self.clearance + self.wall;
+
+        let housing = scad!(Translate(vec3(-self.housing_width() / 2.0, 0.0, 0.0));
+        {
+            scad!(Cube(vec3(self.housing_width(), height, length)))
+        });
+
+        scad!(Union;
+        {
+            housing,
+            self.place_servo(self.servo.mounting_frame(self.wall, self.servo.tab_height))
+        })
+    }
+
+    //Room for the lug and the pin. The pin comes out the front of the mount
+    pub fn cutout(&self, length: f32) -> ScadObject
+    {
+        let slot_start = (length - self.slot_length()) / 2.0;
+
+        //Through the bottom of the mount so the lug can drop out
+        let slot = scad!(Translate(vec3(
+                    -self.lug_width / 2.0 - self.clearance,
+                    -length,
+                    slot_start
+                ));
+        {
+            scad!(Cube(vec3(
+                self.lug_width + self.clearance * 2.0,
+                length + self.lug_height + self.clearance,
+                self.slot_length()
+            )))
+        });
+
+        let pin_end = slot_start + self.slot_length() + self.pin_engagement;
+
+        scad!(Union;
+        {
+            slot,
+            self.pin_cylinder(-1.0, pin_end + 1.0, self.pin_diameter + self.clearance)
+        })
+    }
+}
@@ -0,0 +1,205 @@
+use scad_generator::*;
+
+use DropPod;
+
+//Outline and mounting holes of a circuit board in mm. The board lies flat with its
+//width along x and its length along the pod.
+#[derive(Clone, Copy, Debug)]
+pub struct Board
+{
+    pub name: &'static str,
+
+    pub width: f32,
+    pub length: f32,
+
+    //Hole centers as (x, z) from the corner of the board
+    pub holes: &'static [(f32, f32)],
+    pub hole_diameter: f32,
+}
+
+impl Board
+{
+    pub fn arduino_nano() -> Board
+    {
+        Board {
+            name: "arduino_nano",
+            width: 18.0,
+            length: 45.0,
+            holes: &[(1.38, 0.91), (16.62, 0.91), (1.38, 44.09), (16.62, 44.09)],
+            hole_diameter: 1.8,
+        }
+    }
+
+    pub fn raspberry_pi_pico() -> Board
+    {
+        Board {
+            name: "raspberry_pi_pico",
+            width: 21.0,
+            length: 51.0,
+            holes: &[(4.8, 2.0), (16.2, 2.0), (4.8, 49.0), (16.2, 49.0)],
+            hole_diameter: 2.1,
+        }
+    }
+
+    pub fn feather() -> Board
+    {
+        Board {
+            name: "feather",
+            width: 22.86,
+            length: 50.8,
+            holes: &[(2.54, 2.54), (20.32, 2.54), (2.54, 48.26), (20.32, 48.26)],
+            hole_diameter: 2.5,
+        }
+    }
+
+    pub fn catalog() -> Vec<Board>
+    {
+        vec!(
+            Board::arduino_nano(),
+            Board::raspberry_pi_pico(),
+            Board::feather(),
+        )
+    }
+
+    pub fn by_name(name: &str) -> Option<Board>
+    {
+        Board::catalog().into_iter().find(|board| board.name == name)
+    }
+}
+
+//Rib along the bottom of the sled that runs in a groove in the mid sections so the
+//sled can't turn
+#[derive(Clone, Copy, Debug)]
+pub struct SledKey
+{
+    pub width: f32,
+    //How far the groove goes into the wall
+    pub depth: f32,
+    pub clearance: f32,
+}
+
+impl SledKey
+{
+    //The groove cut into the inside of a section `height` long
+    pub fn groove(&self, inner_radius: f32, height: f32) -> ScadObject
+    {
+        let width = self.width + self.clearance * 2.;
+
+        scad!(Translate(vec3(-width / 2., -inner_radius - self.depth, 0.));
+        {
+            scad!(Cube(vec3(width, self.depth + 1., height)))
+        })
+    }
+}
+
+//A tray that slides into a mid section with standoffs for the boards on top of a deck
+//below the middle of the pod. It is held along the pod by the joints of the neighbouring
+//sections, so it has to be shorter than the space between them.
+pub struct ElectronicsSled
+{
+    pub length: f32,
+    //Distance from the middle of the pod to the top of the deck
+    pub deck_offset: f32,
+    pub thickness: f32,
+    pub clearance: f32,
+
+    //Boards with the distance from the bottom of the sled to their corner
+    pub boards: Vec<(Board, f32)>,
+    pub standoff_height: f32,
+    pub standoff_wall: f32,
+}
+
+impl ElectronicsSled
+{
+    fn radius(&self, pod: &DropPod) -> f32
+    {
+        pod.inner_radius() - self.clearance
+    }
+
+    //Slab across the sled between two heights, cut to the round outline
+    fn slab(&self, pod: &DropPod, bottom: f32, top: f32, length: f32) -> ScadObject
+    {
+        let radius = self.radius(pod);
+
+        scad!(Intersection;
+        {
+            scad!(Cylinder(length, Radius(radius))),
+            scad!(Translate(vec3(-radius, bottom, 0.));
+            {
+                scad!(Cube(vec3(radius * 2., top - bottom, length)))
+            })
+        })
+    }
+
+    fn standoffs(&self, board: &Board, position: f32) -> ScadObject
+    {
+        let outer_diameter = board.hole_diameter + self.standoff_wall * 2.;
+        //Screws cut their own thread into the plastic
+        let pilot_diameter = board.hole_diameter * 0.8;
+
+        let mut result = scad!(Union);
+        for &(x, z) in board.holes
+        {
+            let standoff = scad!(Difference;
+            {
+                scad!(Cylinder(self.standoff_height, Diameter(outer_diameter))),
+                scad!(Cylinder(self.standoff_height + 1., Diameter(pilot_diameter)))
+            });
+
+            //Standing up from the deck
+            result.add_child(scad!(Translate(vec3(x - board.width / 2., -self.deck_offset, position + z));
+            {
+                scad!(Rotate(-90., vec3(1., 0., 0.)); {standoff})
+            }));
+        }
+        result
+    }
+
+    pub fn get(&self, pod: &DropPod) -> ScadObject
+    {
+        let radius = self.radius(pod);
+        let deck_bottom = -self.deck_offset - self.thickness;
+
+        let mut result = scad!(Union;
+        {
+            self.slab(pod, deck_bottom, -self.deck_offset, self.length),
+            //Bulkheads below the deck at the ends
+            self.slab(pod, -radius, deck_bottom, self.thickness),
+            scad!(Translate(vec3(0., 0., self.length - self.thickness));
+            {
+                self.slab(pod, -radius, deck_bottom, self.thickness)
+            }),
+            //Web from the deck to the key
+            scad!(Translate(vec3(-self.thickness / 2., -radius, 0.));
+            {
+                scad!(Cube(vec3(self.thickness, radius - self.deck_offset, self.length)))
+            }),
+        });
+
+        if let Some(key) = pod.sled_key
+        {
+            result.add_child(scad!(Translate(vec3(-key.width / 2., -pod.inner_radius() - key.depth + key.clearance, 0.));
+            {
+                scad!(Cube(vec3(key.width, key.depth - key.clearance + 1., self.length)))
+            }));
+        }
+
+        for &(ref board, position) in &self.boards
+        {
+            //Check that the corners of the board stay inside the pod
+            let board_y = -self.deck_offset + self.standoff_height;
+            if (board.width / 2.).powi(2) + board_y.powi(2) > radius.powi(2)
+            {
+                panic!("The {} board is too wide for the sled", board.name);
+            }
+            if position < 0. || position + board.length > self.length
+            {
+                panic!("The {} board doesn't fit along the sled", board.name);
+            }
+
+            result.add_child(self.standoffs(board, position));
+        }
+
+        result
+    }
+}
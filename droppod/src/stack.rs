@@ -0,0 +1,188 @@
+use std::fmt;
+
+use scad_generator::*;
+
+use {DropPod, Joint, PLA_DENSITY};
+use release::ReleaseLatch;
+use parachute::ParachuteBay;
+use fin_can::FinCan;
+
+//The section at the back of the pod
+pub enum Tail
+{
+    //A nose cone turned around, fitted into the top of the last mid section
+    Cone
+    {
+        height: f32,
+    },
+    FinCan(FinCan),
+}
+
+//A pod described from the nose backwards. The nose cone and the sections behind the mid
+//sections have male joints pointing forwards, so the first mid section has female
+//joints at both ends and the ones after it have a male joint at the bottom.
+pub struct PodStack
+{
+    //Used as the start of the name of the exported files
+    pub name: String,
+    pub pod: DropPod,
+
+    pub nose_height: f32,
+    pub mid_heights: Vec<f32>,
+    //The parachute comes out of the back, so a pod with a bay has no tail
+    pub parachute_bay: Option<ParachuteBay>,
+    pub tail: Option<Tail>,
+
+    //Put on the first mid section
+    pub latch: Option<ReleaseLatch>,
+}
+
+impl PodStack
+{
+    fn check(&self)
+    {
+        if self.mid_heights.is_empty()
+        {
+            panic!("A pod needs at least one mid section");
+        }
+        if self.parachute_bay.is_some() && self.tail.is_some()
+        {
+            panic!("The parachute door has to be at the back of the pod, it can't have a tail");
+        }
+    }
+
+    fn mid_joints(&self, index: usize) -> (Joint, Joint)
+    {
+        if index == 0
+        {
+            (Joint::Female, Joint::Female)
+        }
+        else
+        {
+            (Joint::Male, Joint::Female)
+        }
+    }
+
+    //Length of the assembled pod. The joints are inside the neighbouring sections
+    pub fn length(&self) -> f32
+    {
+        self.check();
+
+        let bay = match self.parachute_bay
+        {
+            Some(ref bay) => bay.length(&self.pod) + bay.door_thickness,
+            None => 0.,
+        };
+
+        let tail = match self.tail
+        {
+            Some(Tail::Cone {height}) => height,
+            Some(Tail::FinCan(ref fin_can)) => fin_can.length,
+            None => 0.,
+        };
+
+        self.nose_height + self.mid_heights.iter().sum::<f32>() + bay + tail
+    }
+
+    //Room for the payload in the mid sections in mm^3
+    pub fn payload_volume(&self) -> f32
+    {
+        self.check();
+
+        self.mid_heights.iter()
+            .enumerate()
+            .map(|(i, height)| {
+                let (bottom, top) = self.mid_joints(i);
+                self.pod.section_payload_volume(*height, bottom, top)
+            })
+            .sum()
+    }
+
+    //Mass in grams of the printed parts, printed solid
+    pub fn empty_mass(&self) -> f32
+    {
+        self.check();
+
+        let pod = &self.pod;
+
+        let mids = self.mid_heights.iter()
+            .enumerate()
+            .map(|(i, height)| {
+                let (bottom, top) = self.mid_joints(i);
+                pod.section_volume(*height, bottom, top)
+            })
+            .sum::<f32>();
+
+        let bay = match self.parachute_bay
+        {
+            Some(ref bay) => bay.material_volume(pod),
+            None => 0.,
+        };
+
+        let tail = match self.tail
+        {
+            Some(Tail::Cone {height}) => pod.nose_cone_volume(height),
+            Some(Tail::FinCan(ref fin_can)) => fin_can.material_volume(pod),
+            None => 0.,
+        };
+
+        (pod.nose_cone_volume(self.nose_height) + mids + bay + tail) * PLA_DENSITY
+    }
+
+    //Every printed part with its name, each standing on its bottom
+    pub fn sections(&self) -> Vec<(String, ScadObject)>
+    {
+        self.check();
+
+        let pod = &self.pod;
+        let mut result = vec!();
+
+        result.push((String::from("nose_cone"), pod.get_nose_cone(self.nose_height)));
+
+        for (i, height) in self.mid_heights.iter().enumerate()
+        {
+            let (bottom, top) = self.mid_joints(i);
+            let latch = if i == 0 {self.latch.as_ref()} else {None};
+
+            result.push((format!("mid_{}", i + 1), pod.get_section(*height, bottom, top, latch)));
+        }
+
+        if let Some(ref bay) = self.parachute_bay
+        {
+            result.push((String::from("parachute_bay"), bay.get(pod)));
+            result.push((String::from("parachute_door"), bay.get_door(pod)));
+        }
+
+        match self.tail
+        {
+            Some(Tail::Cone {height}) => result.push((String::from("tail_cone"), pod.get_tail_cone(height))),
+            Some(Tail::FinCan(ref fin_can)) => result.push((String::from("fin_can"), fin_can.get(pod))),
+            None => {}
+        }
+
+        result
+    }
+
+    //Writes every section to <name>_<section>_auto.scad
+    pub fn write_sections(&self, detail: i32)
+    {
+        for (section, object) in self.sections()
+        {
+            let mut file = ScadFile::new();
+            file.set_detail(detail);
+            file.add_object(object);
+            file.write_to_file(format!("{}_{}_auto.scad", self.name, section));
+        }
+    }
+}
+
+impl fmt::Display for PodStack
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        writeln!(f, "{} pod:", self.name)?;
+        writeln!(f, "  Length: {:.1} mm", self.length())?;
+        writeln!(f, "  Payload volume: {:.1} cm^3", self.payload_volume() / 1000.)?;
+        writeln!(f, "  Empty mass: {:.0} g", self.empty_mass())
+    }
+}
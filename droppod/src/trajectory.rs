@@ -0,0 +1,129 @@
+use std::fmt;
+
+use na;
+
+use parachute::{AIR_DENSITY, GRAVITY};
+
+//Time step of the simulation in seconds
+const TIME_STEP: f32 = 0.005;
+//Gives up on drops that take longer than this
+const MAX_TIME: f32 = 600.;
+
+//Rough drag coefficient of a cylinder flying nose first. A flat front is around 0.8
+//and it drops towards 0.15 as the nose gets longer compared to the diameter
+pub fn drag_coefficient(nose_length: f32, diameter: f32) -> f32
+{
+    let fineness = nose_length / diameter;
+    0.15 + 0.65 * (-2.5 * fineness).exp()
+}
+
+//A parachute that opens `deploy_delay` seconds after the release
+#[derive(Clone, Copy, Debug)]
+pub struct ParachuteDeployment
+{
+    //Drag coefficient times area in mm^2
+    pub drag_area: f32,
+    pub deploy_delay: f32,
+}
+
+//Speeds are in m/s and distances in m. x is along the flight path of the plane, y to
+//the right of it and z up
+pub struct DropSimulation
+{
+    //Mass in grams
+    pub mass: f32,
+    //Drag coefficient times frontal area of the pod in mm^2
+    pub drag_area: f32,
+    pub parachute: Option<ParachuteDeployment>,
+
+    pub altitude: f32,
+    pub airspeed: f32,
+    //Wind along the flight path, positive is a tailwind, and wind from the left
+    pub wind: na::Vector2<f32>,
+}
+
+pub struct DropResult
+{
+    pub fall_time: f32,
+    //Where the pod lands relative to where it was released
+    pub downrange: f32,
+    pub drift: f32,
+    pub impact_speed: f32,
+}
+
+impl DropSimulation
+{
+    fn drag_area_at(&self, time: f32) -> f32
+    {
+        let parachute = match self.parachute
+        {
+            Some(ref parachute) if time >= parachute.deploy_delay => parachute.drag_area,
+            _ => 0.,
+        };
+
+        (self.drag_area + parachute) / 1_000_000.
+    }
+
+    fn acceleration(&self, velocity: na::Vector3<f32>, time: f32) -> na::Vector3<f32>
+    {
+        let wind = na::Vector3::new(self.wind.x, self.wind.y, 0.);
+        let air_velocity = velocity - wind;
+
+        let drag = -air_velocity * air_velocity.norm()
+            * (0.5 * AIR_DENSITY * self.drag_area_at(time) / (self.mass / 1000.));
+
+        drag + na::Vector3::new(0., 0., -GRAVITY)
+    }
+
+    //Runs the drop with a fourth order Runge-Kutta integration until the pod hits the ground
+    pub fn simulate(&self) -> DropResult
+    {
+        let mut position = na::Vector3::new(0., 0., self.altitude);
+        //The pod starts with the speed of the plane over the ground
+        let mut velocity = na::Vector3::new(self.airspeed + self.wind.x, self.wind.y, 0.);
+        let mut time = 0.;
+
+        while position.z > 0. && time < MAX_TIME
+        {
+            let dt = TIME_STEP;
+            let half = dt / 2.;
+
+            let a1 = self.acceleration(velocity, time);
+            let a2 = self.acceleration(velocity + a1 * half, time + half);
+            let a3 = self.acceleration(velocity + a2 * half, time + half);
+            let a4 = self.acceleration(velocity + a3 * dt, time + dt);
+
+            let v2 = velocity + a1 * half;
+            let v3 = velocity + a2 * half;
+            let v4 = velocity + a3 * dt;
+
+            position += (velocity + v2 * 2. + v3 * 2. + v4) * (dt / 6.);
+            velocity += (a1 + a2 * 2. + a3 * 2. + a4) * (dt / 6.);
+            time += dt;
+        }
+
+        DropResult {
+            fall_time: time,
+            downrange: position.x,
+            drift: position.y,
+            impact_speed: velocity.norm(),
+        }
+    }
+}
+
+impl fmt::Display for DropResult
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        writeln!(f, "Time of fall: {:.1} s", self.fall_time)?;
+        writeln!(f, "Impact speed: {:.1} m/s", self.impact_speed)?;
+        writeln!(
+            f,
+            "Release {:.1} m {} the target and {:.1} m to the {} of it",
+            self.downrange.abs(),
+            if self.downrange > 0. {"before"} else {"after"},
+            self.drift.abs(),
+            if self.drift > 0. {"left"} else {"right"}
+        )
+    }
+}
@@ -0,0 +1,113 @@
+//NACA 4 digit airfoil. All values are fractions of the chord, so NACA 2412 has a
+//camber of 0.02 at 0.4 and a thickness of 0.12
+#[derive(Clone, Copy, Debug)]
+pub struct Naca4
+{
+    pub camber: f32,
+    pub camber_position: f32,
+    pub thickness: f32,
+}
+
+impl Naca4
+{
+    pub fn new(digits: &str) -> Naca4
+    {
+        let digit = |i: usize| digits[i..i + 1].parse::<f32>().unwrap();
+
+        Naca4 {
+            camber: digit(0) / 100.0,
+            camber_position: digit(1) / 10.0,
+            thickness: digits[2..4].parse::<f32>().unwrap() / 100.0,
+        }
+    }
+
+    //Half thickness at `x`, using the closed trailing edge coefficient
+    fn half_thickness(&self, x: f32) -> f32
+    {
+        5.0 * self.thickness * (
+            0.2969 * x.sqrt()
+            - 0.1260 * x
+            - 0.3516 * x.powi(2)
+            + 0.2843 * x.powi(3)
+            - 0.1036 * x.powi(4)
+        )
+    }
+
+    pub fn thickness_at(&self, x: f32) -> f32
+    {
+        self.half_thickness(x) * 2.0
+    }
+
+    //Height of the camber line at `x`
+    pub fn camber_at(&self, x: f32) -> f32
+    {
+        let (m, p) = (self.camber, self.camber_position);
+
+        if m == 0.0
+        {
+            0.0
+        }
+        else if x < p
+        {
+            m / (p * p) * (2.0 * p * x - x * x)
+        }
+        else
+        {
+            m / ((1.0 - p) * (1.0 - p)) * (1.0 - 2.0 * p + 2.0 * p * x - x * x)
+        }
+    }
+
+    fn camber_slope(&self, x: f32) -> f32
+    {
+        let (m, p) = (self.camber, self.camber_position);
+
+        if m == 0.0
+        {
+            0.0
+        }
+        else if x < p
+        {
+            2.0 * m / (p * p) * (p - x)
+        }
+        else
+        {
+            2.0 * m / ((1.0 - p) * (1.0 - p)) * (p - x)
+        }
+    }
+
+    //Points around the airfoil scaled to `chord`, counterclockwise from the trailing edge
+    //over the top. x goes along the chord and y up
+    pub fn outline(&self, chord: f32, points_per_side: usize) -> Vec<(f32, f32)>
+    {
+        //Cosine spacing puts more points around the leading edge
+        let xs = (0..points_per_side + 1)
+            .map(|i| {
+                let angle = i as f32 / points_per_side as f32 * std::f32::consts::PI;
+                (1.0 - angle.cos()) / 2.0
+            })
+            .collect::<Vec<_>>();
+
+        let surface_point = |x: f32, side: f32| {
+            let thickness = self.half_thickness(x);
+            let angle = self.camber_slope(x).atan();
+
+            (
+                (x - side * thickness * angle.sin()) * chord,
+                (self.camber_at(x) + side * thickness * angle.cos()) * chord
+            )
+        };
+
+        let mut result = vec!();
+        //The upper surface from the trailing edge to the leading edge
+        for x in xs.iter().rev()
+        {
+            result.push(surface_point(*x, 1.0));
+        }
+        //The lower surface back to the trailing edge, skipping the shared end points
+        for x in &xs[1..xs.len() - 1]
+        {
+            result.push(surface_point(*x, -1.0));
+        }
+        result
+    }
+}
@@ -0,0 +1,172 @@
+use scad_generator::*;
+
+use loft::Loft;
+
+//The glider is set up for lead, steel shot is the cheaper fallback
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug)]
+pub enum BallastMaterial
+{
+    Lead,
+    Steel,
+}
+
+impl BallastMaterial
+{
+    //Density in g/mm^3
+    pub fn density(&self) -> f32
+    {
+        match *self
+        {
+            BallastMaterial::Lead => 0.01134,
+            BallastMaterial::Steel => 0.00785,
+        }
+    }
+}
+
+//A tube along the fuselage centered on the CG so that adding ballast doesn't move it.
+//The tube is closed and loaded with shot through a hole from the bottom of the fuselage
+//into its rear end, which is tapped for a plug screw.
+pub struct BallastChamber
+{
+    //Mass of the ballast in grams
+    pub mass: f32,
+    pub material: BallastMaterial,
+
+    pub diameter: f32,
+    pub wall_thickness: f32,
+
+    pub cg_station: f32,
+    //Height of the center of the tube relative to the fuselage axis
+    pub height_offset: f32,
+
+    //Diameter of the fill hole, tapped for the plug screw
+    pub fill_diameter: f32,
+}
+
+//Length of the fill hole and its boss below the tube. They are cut off by the outer shape
+//of the fuselage so they only need to be long enough to reach the wall
+const FILL_HOLE_LENGTH: f32 = 60.0;
+
+impl BallastChamber
+{
+    //Inside length of the tube needed for the ballast
+    pub fn length(&self) -> f32
+    {
+        let volume = self.mass / self.material.density();
+        let area = std::f32::consts::PI * (self.diameter / 2.0).powi(2);
+
+        volume / area
+    }
+
+    fn start(&self) -> f32
+    {
+        self.cg_station - self.length() / 2.0
+    }
+
+    //Station of the fill hole, as far back as it fits inside the tube
+    pub fn fill_station(&self) -> f32
+    {
+        self.start() + self.length() - self.fill_diameter / 2.0
+    }
+
+    //Cylinder going down from the center of the tube at the fill station
+    fn fill_column(&self, diameter: f32) -> ScadObject
+    {
+        scad!(Translate(vec3(0.0, self.height_offset - FILL_HOLE_LENGTH, self.fill_station()));
+        {
+            scad!(Rotate(-90.0, vec3(1.0, 0.0, 0.0));
+            {
+                scad!(Cylinder(FILL_HOLE_LENGTH, Diameter(diameter)))
+            })
+        })
+    }
+
+    //Adds the tube to the fuselage shell. `outer_shape` keeps the tube inside the fuselage
+    pub fn apply(&self, shell: ScadObject, outer_shape: ScadObject) -> ScadObject
+    {
+        let length = self.length();
+
+        let tube = scad!(Union;
+        {
+            scad!(Translate(vec3(0.0, self.height_offset, self.start() - self.wall_thickness));
+            {
+                scad!(Cylinder(length + self.wall_thickness * 2.0, Diameter(self.diameter + self.wall_thickness * 2.0)))
+            }),
+            self.fill_column(self.fill_diameter + self.wall_thickness * 2.0)
+        });
+
+        let bore = scad!(Union;
+        {
+            scad!(Translate(vec3(0.0, self.height_offset, self.start()));
+            {
+                scad!(Cylinder(length, Diameter(self.diameter)))
+            }),
+            self.fill_column(self.fill_diameter)
+        });
+
+        scad!(Difference;
+        {
+            scad!(Union;{shell, scad!(Intersection;{tube, outer_shape})}),
+            bore
+        })
+    }
+}
+
+//A reinforced pad under the fuselage with a threaded hole for a tow or hi-start hook.
+//The hook sits `angle` degrees ahead of the CG, seen from the CG on the fuselage axis.
+pub struct TowHook
+{
+    pub cg_station: f32,
+    pub angle: f32,
+
+    pub pad_width: f32,
+    pub pad_length: f32,
+    pub pad_thickness: f32,
+
+    pub screw_diameter: f32,
+}
+
+impl TowHook
+{
+    //Station of the hook on the bottom of the fuselage
+    pub fn station(&self, outer: &Loft) -> f32
+    {
+        let bottom = match outer.section_at(self.cg_station)
+        {
+            Some(section) => section.height / 2.0 - section.vertical_offset,
+            None => panic!("The CG at {} is outside the fuselage", self.cg_station),
+        };
+
+        self.cg_station - bottom * self.angle.to_radians().tan()
+    }
+
+    pub fn apply(&self, shell: ScadObject, outer: &Loft) -> ScadObject
+    {
+        let station = self.station(outer);
+        let bottom = match outer.section_at(station)
+        {
+            Some(section) => section.vertical_offset - section.height / 2.0,
+            None => panic!("The tow hook at {} is outside the fuselage", station),
+        };
+
+        let pad = scad!(Translate(vec3(-self.pad_width / 2.0, bottom, station - self.pad_length / 2.0));
+        {
+            scad!(Cube(vec3(self.pad_width, self.pad_thickness, self.pad_length)))
+        });
+
+        let hole = scad!(Translate(vec3(0.0, bottom - 1.0, station));
+        {
+            scad!(Rotate(-90.0, vec3(1.0, 0.0, 0.0));
+            {
+                scad!(Cylinder(self.pad_thickness + 2.0, Diameter(self.screw_diameter)))
+            })
+        });
+
+        scad!(Difference;
+        {
+            scad!(Union;{shell, scad!(Intersection;{pad, outer.get()})}),
+            hole
+        })
+    }
+}
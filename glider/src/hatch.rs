@@ -0,0 +1,293 @@
+use na;
+use scad_generator::*;
+
+use rc_common::assembly::Part;
+
+use loft::Loft;
+use CUTTER_SIZE;
+
+//Largest size of the collision boxes along the curved lip
+const LIP_BOX_SIZE: f32 = 2.0;
+
+//An access hatch cut out of the top of the fuselage. Everything above `cut_height`
+//between `start` and `end` becomes a separate lid, so the edge of the hatch follows
+//the surface of the fuselage.
+pub struct Hatch
+{
+    pub start: f32,
+    pub end: f32,
+    pub cut_height: f32,
+
+    //The lip is a band along the inside of the wall that the lid rests on. It goes
+    //`lip_width` into the fuselage and `lip_depth` below the cut
+    pub lip_width: f32,
+    pub lip_depth: f32,
+    //Stations between which the lip along the sides is left out, to make room for parts
+    //that are put in from above and reach up to the cut
+    pub lip_gaps: Vec<(f32, f32)>,
+
+    //Gap between the lid and the body along the stations
+    pub clearance: f32,
+
+    //Magnets go on both sides of the hatch at each station
+    pub magnet_stations: Vec<f32>,
+    pub magnet_diameter: f32,
+    pub magnet_height: f32,
+    //Wall around the magnet pockets
+    pub magnet_wall: f32,
+}
+
+impl Hatch
+{
+    fn cutter(&self, start: f32, end: f32) -> ScadObject
+    {
+        scad!(Translate(vec3(-CUTTER_SIZE / 2.0, self.cut_height, start));
+        {
+            scad!(Cube(vec3(CUTTER_SIZE, CUTTER_SIZE, end - start)))
+        })
+    }
+
+    fn boss_radius(&self) -> f32
+    {
+        self.magnet_diameter / 2.0 + self.magnet_wall
+    }
+
+    //Centers of the magnets at the cut height
+    fn magnet_positions(&self, inner: &Loft) -> Vec<(f32, f32)>
+    {
+        let mut result = vec!();
+
+        //The bosses have to fit between the end bands of the lip
+        let first = self.start + self.lip_width + self.boss_radius();
+        let last = self.end - self.lip_width - self.boss_radius();
+
+        for station in &self.magnet_stations
+        {
+            if *station < first || *station > last
+            {
+                panic!("Magnet boss at station {} overlaps the lip, the stations have to be between {} and {}", station, first, last);
+            }
+
+            let half_width = match inner.section_at(*station).and_then(|s| s.half_width_at(self.cut_height))
+            {
+                Some(half_width) => half_width,
+                None => panic!("Magnet at station {} is outside the hatch cut", station),
+            };
+
+            let x = half_width - self.boss_radius();
+            result.push((x, *station));
+            result.push((-x, *station));
+        }
+        result
+    }
+
+    //Cylinders along y starting at the cut height, going up if `up` is set
+    fn magnet_cylinders(&self, inner: &Loft, diameter: f32, length: f32, up: bool) -> ScadObject
+    {
+        let mut result = scad!(Union);
+
+        for (x, z) in self.magnet_positions(inner)
+        {
+            let y = if up {self.cut_height} else {self.cut_height - length};
+
+            result.add_child(scad!(Translate(vec3(x, y, z));
+            {
+                scad!(Rotate(-90.0, vec3(1.0, 0.0, 0.0));
+                {
+                    scad!(Cylinder(length, Diameter(diameter)))
+                })
+            }));
+        }
+        result
+    }
+
+    //The lid bosses go up to the shell of the lid while the bosses on the body side
+    //only go deep enough for the magnets and hang on to the lip
+    fn bosses(&self, outer: &Loft, inner: &Loft, up: bool) -> ScadObject
+    {
+        let diameter = self.boss_radius() * 2.0;
+        let length = if up {CUTTER_SIZE} else {self.magnet_height + self.magnet_wall};
+
+        scad!(Intersection;
+        {
+            self.magnet_cylinders(inner, diameter, length, up),
+            outer.get()
+        })
+    }
+
+    //Stations of the lip along the sides, split around the gaps
+    fn lip_spans(&self) -> Vec<(f32, f32)>
+    {
+        let mut result = vec!((self.start - self.lip_width, self.end + self.lip_width));
+
+        for &(gap_start, gap_end) in &self.lip_gaps
+        {
+            result = result.into_iter()
+                .flat_map(|(start, end)| vec!((start, end.min(gap_start)), (start.max(gap_end), end)))
+                .filter(|&(start, end)| end > start)
+                .collect();
+        }
+        result
+    }
+
+    //Boxes covering the band between the inside of the wall and the lip edge from `start`
+    //to `end` and from `bottom` to `top`
+    fn band_boxes(&self, inner: &Loft, stations: (f32, f32), heights: (f32, f32))
+        -> Vec<(na::Vector3<f32>, na::Vector3<f32>)>
+    {
+        //Splits the range from `start` to `end` into pieces no longer than LIP_BOX_SIZE
+        let split = |(start, end): (f32, f32)| {
+            let steps = ((end - start) / LIP_BOX_SIZE).ceil().max(1.0) as usize;
+
+            (0..steps)
+                .map(|i| (
+                    start + (end - start) * i as f32 / steps as f32,
+                    start + (end - start) * (i + 1) as f32 / steps as f32
+                ))
+                .collect::<Vec<_>>()
+        };
+
+        let edge = inner.inset(self.lip_width);
+
+        let mut result = vec!();
+        for (z0, z1) in split(stations)
+        {
+            for (y0, y1) in split(heights)
+            {
+                let half_widths = |loft: &Loft| [(z0, y0), (z0, y1), (z1, y0), (z1, y1)].iter()
+                    .map(|&(z, y)| loft.section_at(z).and_then(|s| s.half_width_at(y)).unwrap_or(0.0))
+                    .collect::<Vec<_>>();
+
+                let outside = half_widths(inner).into_iter().fold(0.0, f32::max);
+                let inside = half_widths(&edge).into_iter().fold(std::f32::MAX, f32::min);
+
+                if outside == 0.0
+                {
+                    continue;
+                }
+
+                //Above the inner edge the band goes across the whole fuselage
+                if inside == 0.0
+                {
+                    result.push((vec3(-outside, y0, z0), vec3(outside * 2.0, y1 - y0, z1 - z0)));
+                }
+                else
+                {
+                    result.push((vec3(inside, y0, z0), vec3(outside - inside, y1 - y0, z1 - z0)));
+                    result.push((vec3(-outside, y0, z0), vec3(outside - inside, y1 - y0, z1 - z0)));
+                }
+            }
+        }
+        result
+    }
+
+    //The lip with boxes covering it as collision volumes
+    pub fn lip_part(&self, inner: &Loft) -> Part
+    {
+        let below_cut = (self.cut_height - self.lip_depth, self.cut_height);
+
+        let mut boxes = vec!();
+        for span in self.lip_spans()
+        {
+            boxes.extend(self.band_boxes(inner, span, below_cut));
+        }
+
+        //The ends of the lip go over the top of the fuselage
+        let top = |z: f32| inner.section_at(z).map(|s| s.vertical_offset + s.height / 2.0).unwrap_or(self.cut_height);
+        for &station in &[self.start, self.end]
+        {
+            let span = (station - self.lip_width, station + self.lip_width);
+            let height = top(span.0).max(top(span.1));
+
+            boxes.extend(self.band_boxes(inner, span, (self.cut_height, height)));
+        }
+
+        boxes.into_iter().fold(
+            Part::new("hatch_lip", self.lip(inner), vec3(0.8, 0.8, 0.2)),
+            |part, (min, size)| part.with_volume(min, size)
+        )
+    }
+
+    //The magnet bosses on the body side with a box around each as collision volumes
+    pub fn boss_part(&self, outer: &Loft, inner: &Loft) -> Part
+    {
+        let (radius, length) = (self.boss_radius(), self.magnet_height + self.magnet_wall);
+
+        self.magnet_positions(inner).into_iter().fold(
+            Part::new("hatch_bosses", self.bosses(outer, inner, false), vec3(0.8, 0.8, 0.2)),
+            |part, (x, z)| part.with_volume(
+                vec3(x - radius, self.cut_height - length, z - radius),
+                vec3(radius * 2.0, length, radius * 2.0)
+            )
+        )
+    }
+
+    fn lip(&self, inner: &Loft) -> ScadObject
+    {
+        let band = scad!(Difference;
+        {
+            inner.get(),
+            inner.inset(self.lip_width).get()
+        });
+
+        let region = scad!(Translate(vec3(
+                    -CUTTER_SIZE / 2.0,
+                    self.cut_height - self.lip_depth,
+                    self.start - self.lip_width
+                ));
+        {
+            scad!(Cube(vec3(
+                CUTTER_SIZE,
+                CUTTER_SIZE,
+                self.end - self.start + self.lip_width * 2.0
+            )))
+        });
+
+        //The band under the middle of the lid would be in the way of the opening
+        let mut openings = scad!(Union;{self.cutter(self.start + self.lip_width, self.end - self.lip_width)});
+        for &(gap_start, gap_end) in &self.lip_gaps
+        {
+            openings.add_child(scad!(Translate(vec3(-CUTTER_SIZE / 2.0, self.cut_height - self.lip_depth - 1.0, gap_start));
+            {
+                scad!(Cube(vec3(CUTTER_SIZE, self.lip_depth + 1.0, gap_end - gap_start)))
+            }));
+        }
+
+        scad!(Difference;
+        {
+            scad!(Intersection;{band, region}),
+            openings
+        })
+    }
+
+    //The fuselage with the hatch cut out and the lip added. `shell` is the fuselage,
+    //`outer` and `inner` are the outside and inside surfaces of its wall
+    pub fn body(&self, shell: ScadObject, outer: &Loft, inner: &Loft) -> ScadObject
+    {
+        scad!(Difference;
+        {
+            scad!(Union;
+            {
+                scad!(Difference;{shell, self.cutter(self.start, self.end)}),
+                self.lip(inner),
+                self.bosses(outer, inner, false),
+            }),
+            self.magnet_cylinders(inner, self.magnet_diameter, self.magnet_height, false)
+        })
+    }
+
+    pub fn lid(&self, shell: ScadObject, outer: &Loft, inner: &Loft) -> ScadObject
+    {
+        let cutter = self.cutter(self.start + self.clearance, self.end - self.clearance);
+
+        scad!(Difference;
+        {
+            scad!(Intersection;
+            {
+                scad!(Union;{shell, self.bosses(outer, inner, true)}),
+                cutter
+            }),
+            self.magnet_cylinders(inner, self.magnet_diameter, self.magnet_height, true)
+        })
+    }
+}
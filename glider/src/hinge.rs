@@ -0,0 +1,219 @@
+use scad_generator::*;
+
+use tail::TailSurface;
+use CUTTER_SIZE;
+
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug)]
+pub enum HingeType
+{
+    //Knuckles printed around a pin that is part of the fixed half
+    PrintInPlace
+    {
+        pin_diameter: f32,
+        knuckles: usize,
+    },
+    //Knuckles with a hole for a piece of filament or wire
+    Pin
+    {
+        pin_diameter: f32,
+        knuckles: usize,
+    },
+    //A thin web along the bottom skin with a groove above it. The two halves stay one part
+    Living
+    {
+        web_thickness: f32,
+    },
+}
+
+//Deflections are in degrees, up is the trailing edge going up
+#[derive(Clone, Copy, Debug)]
+pub struct Hinge
+{
+    pub hinge_type: HingeType,
+    pub clearance: f32,
+    pub max_up: f32,
+    pub max_down: f32,
+}
+
+//The parts of a surface after splitting it along the hinge line. The control surface is
+//None when it is still attached to the fixed part
+pub struct HingedSurface
+{
+    pub fixed: ScadObject,
+    pub moving: Option<ScadObject>,
+}
+
+impl Hinge
+{
+    //Moves an object from the hinge coordinates, where the hinge line goes along x from the
+    //origin with the chord along z, to the coordinates of the surface
+    fn to_surface(surface: &TailSurface, object: ScadObject) -> ScadObject
+    {
+        let (start, end) = surface.hinge_line();
+        let direction = end - start;
+
+        scad!(Translate(start);
+        {
+            scad!(Rotate((-direction.z).atan2(direction.x).to_degrees(), vec3(0.0, 1.0, 0.0));
+            {
+                object
+            })
+        })
+    }
+
+    fn hinge_length(surface: &TailSurface) -> f32
+    {
+        let (start, end) = surface.hinge_line();
+        (end - start).norm()
+    }
+
+    //Everything on one side of a plane through the hinge line at `angle` degrees from
+    //the chord. `above` keeps the side the trailing edge turns towards when going up
+    fn half_space(angle: f32, above: bool) -> ScadObject
+    {
+        let y = if above {0.0} else {-CUTTER_SIZE};
+
+        scad!(Rotate(-angle, vec3(1.0, 0.0, 0.0));
+        {
+            scad!(Translate(vec3(-CUTTER_SIZE / 2.0, y, -CUTTER_SIZE / 2.0));
+            {
+                scad!(Cube(vec3(CUTTER_SIZE, CUTTER_SIZE, CUTTER_SIZE)))
+            })
+        })
+    }
+
+    //The part of the control surface that can't hit the fixed part within the deflection
+    //range. The leading edge gets a bevel on both sides
+    fn bevel(&self) -> ScadObject
+    {
+        scad!(Intersection;
+        {
+            Hinge::half_space(90.0 - self.max_up, false),
+            Hinge::half_space(self.max_down - 90.0, true)
+        })
+    }
+
+    //Cylinder along the hinge line
+    fn cylinder(start: f32, length: f32, diameter: f32) -> ScadObject
+    {
+        scad!(Translate(vec3(start, 0.0, 0.0));
+        {
+            scad!(Rotate(90.0, vec3(0.0, 1.0, 0.0));
+            {
+                scad!(Cylinder(length, Diameter(diameter)))
+            })
+        })
+    }
+
+    //Every other knuckle starting with knuckle `first`. `grow` makes them larger by the
+    //clearance for cutting room for them out of the other half
+    fn knuckles(&self, length: f32, count: usize, first: usize, diameter: f32, grow: bool) -> ScadObject
+    {
+        let segment = length / count as f32;
+        let mut result = scad!(Union);
+
+        for i in (first..count).step_by(2)
+        {
+            let (start, length, diameter) = if grow
+            {
+                (i as f32 * segment - self.clearance, segment + self.clearance * 2.0, diameter + self.clearance * 2.0)
+            }
+            else
+            {
+                (i as f32 * segment + self.clearance / 2.0, segment - self.clearance, diameter)
+            };
+
+            result.add_child(Hinge::cylinder(start, length, diameter));
+        }
+        result
+    }
+
+    fn knuckle_hinge(&self, surface: &TailSurface, pin_diameter: f32, count: usize, printed_pin: bool) -> HingedSurface
+    {
+        let length = Hinge::hinge_length(surface);
+        //The surface is thinnest at the tip which limits the size of the knuckles
+        let diameter = surface.hinge_thickness(surface.span);
+
+        let fixed = scad!(Difference;
+        {
+            scad!(Union;
+            {
+                surface.fixed(),
+                Hinge::to_surface(surface, self.knuckles(length, count, 0, diameter, false))
+            }),
+            Hinge::to_surface(surface, self.knuckles(length, count, 1, diameter, true))
+        });
+
+        let moving = scad!(Difference;
+        {
+            scad!(Union;
+            {
+                scad!(Intersection;
+                {
+                    surface.control_surface(),
+                    Hinge::to_surface(surface, self.bevel())
+                }),
+                Hinge::to_surface(surface, self.knuckles(length, count, 1, diameter, false))
+            }),
+            Hinge::to_surface(surface, self.knuckles(length, count, 0, diameter, true))
+        });
+
+        //The pin goes past both ends of the hinge line to make sure it is cut through
+        let bore = |diameter: f32| Hinge::to_surface(surface, Hinge::cylinder(-1.0, length + 2.0, diameter));
+
+        if printed_pin
+        {
+            HingedSurface {
+                fixed: scad!(Union;
+                {
+                    fixed,
+                    Hinge::to_surface(surface, Hinge::cylinder(0.0, length, pin_diameter))
+                }),
+                moving: Some(scad!(Difference;{moving, bore(pin_diameter + self.clearance * 2.0)})),
+            }
+        }
+        else
+        {
+            HingedSurface {
+                fixed: scad!(Difference;{fixed, bore(pin_diameter + self.clearance)}),
+                moving: Some(scad!(Difference;{moving, bore(pin_diameter + self.clearance)})),
+            }
+        }
+    }
+
+    //The web is at the bottom of the surface where it is thinnest. Further in the surface
+    //is thicker and so is the web
+    fn living_hinge(&self, surface: &TailSurface, web_thickness: f32) -> HingedSurface
+    {
+        let web_height = web_thickness - surface.hinge_thickness(surface.span) / 2.0;
+
+        //The top skin on both sides of the groove touches when the surface is fully up
+        let groove = scad!(Translate(vec3(0.0, web_height, 0.0));
+        {
+            scad!(Intersection;
+            {
+                Hinge::half_space(90.0 - self.max_up / 2.0, true),
+                Hinge::half_space(90.0 + self.max_up / 2.0, false)
+            })
+        });
+
+        HingedSurface {
+            fixed: scad!(Difference;{surface.shape(), Hinge::to_surface(surface, groove)}),
+            moving: None,
+        }
+    }
+
+    pub fn split(&self, surface: &TailSurface) -> HingedSurface
+    {
+        match self.hinge_type
+        {
+            HingeType::PrintInPlace {pin_diameter, knuckles} =>
+                self.knuckle_hinge(surface, pin_diameter, knuckles, true),
+            HingeType::Pin {pin_diameter, knuckles} =>
+                self.knuckle_hinge(surface, pin_diameter, knuckles, false),
+            HingeType::Living {web_thickness} =>
+                self.living_hinge(surface, web_thickness),
+        }
+    }
+}
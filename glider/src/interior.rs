@@ -0,0 +1,242 @@
+use na;
+use scad_generator::*;
+
+use rc_common::servo::ServoSpec;
+use rc_common::assembly::Part;
+
+//The fuselage is built along z with the nose at z=0. Servos lie with their long side
+//along the fuselage and the output shaft pointing up along y.
+
+//Height of the horn above the top of the servo body
+const HORN_HEIGHT: f32 = 3.0;
+
+//Length of the screw bosses below the tabs. They are cut off by the outer shape of the
+//fuselage so they only need to be long enough to reach the wall
+const BOSS_LENGTH: f32 = 60.0;
+
+//Clearance between the servo and the pocket it sits in
+const POCKET_CLEARANCE: f32 = 0.3;
+
+pub struct ServoPlacement
+{
+    pub servo: ServoSpec,
+    //Position of the center of the servo along the fuselage
+    pub station: f32,
+    //Sideways offset of the center of the servo
+    pub side_offset: f32,
+    //Height of the bottom of the servo relative to the fuselage axis
+    pub height_offset: f32,
+
+    //Distance from the output shaft to the pushrod hole in the servo arm. Negative
+    //values put the hole on the other side of the servo
+    pub arm_length: f32,
+}
+
+impl ServoPlacement
+{
+    //Moves an object from the coordinates of the servo to the fuselage
+    fn place(&self, object: ScadObject) -> ScadObject
+    {
+        scad!(Translate(vec3(self.side_offset, self.height_offset, self.station));
+        {
+            scad!(Rotate(-90.0, vec3(0.0, 0.0, 1.0));
+            {
+                scad!(Rotate(-90.0, vec3(0.0, 1.0, 0.0));
+                {
+                    scad!(Translate(vec3(0.0, -self.servo.body_size.y / 2.0, 0.0));
+                    {
+                        object
+                    })
+                })
+            })
+        })
+    }
+
+    //Same transformation as `place` for a single point
+    fn place_point(&self, point: na::Vector3<f32>) -> na::Vector3<f32>
+    {
+        vec3(
+            point.y - self.servo.body_size.y / 2.0 + self.side_offset,
+            point.z + self.height_offset,
+            point.x + self.station
+        )
+    }
+
+    pub fn shaft_position(&self) -> na::Vector3<f32>
+    {
+        self.place_point(vec3(self.servo.shaft_offset, self.servo.body_size.y / 2.0, self.servo.body_size.z))
+    }
+
+    //Position of the pushrod hole in the servo arm
+    pub fn horn_position(&self) -> na::Vector3<f32>
+    {
+        let size = self.servo.body_size;
+
+        self.place_point(vec3(
+            self.servo.shaft_offset,
+            size.y / 2.0 + self.arm_length,
+            size.z + HORN_HEIGHT
+        ))
+    }
+
+    //Stations of the front and back of the servo including its tabs, made `clearance`
+    //longer at both ends
+    pub fn station_range(&self, clearance: f32) -> (f32, f32)
+    {
+        let half_length = self.servo.tab_length.max(self.servo.body_size.x) / 2.0 + clearance;
+
+        (self.station - half_length, self.station + half_length)
+    }
+
+    //The servo in the fuselage with its body and tabs as collision volumes
+    pub fn part(&self, name: &str) -> Part
+    {
+        let mut part = Part::new(name, self.place(self.servo.keep_out(0.0)), vec3(0.2, 0.2, 0.8));
+
+        //`place_point` only swaps the axes around so the boxes stay aligned with them
+        for &(min, size) in &self.servo.boxes()
+        {
+            part = part.with_volume(self.place_point(min), vec3(size.y, size.z, size.x));
+        }
+        part
+    }
+
+    fn pocket(&self) -> ScadObject
+    {
+        self.place(scad!(Union;
+        {
+            self.servo.envelope(POCKET_CLEARANCE),
+            self.servo.pilot_holes()
+        }))
+    }
+
+    //Columns from the tabs down to the wall that the servo is screwed into
+    fn bosses(&self) -> ScadObject
+    {
+        let mut result = scad!(Union);
+
+        for x in &self.servo.screw_positions()
+        {
+            result.add_child(scad!(Translate(vec3(*x, self.servo.body_size.y / 2.0, self.servo.tab_height - BOSS_LENGTH));
+            {
+                scad!(Cylinder(BOSS_LENGTH, Diameter(self.servo.screw_diameter * 3.0)))
+            }));
+        }
+
+        self.place(result)
+    }
+}
+
+//A tube for a pushrod following a quadratic bezier curve from `start` to `end`. With
+//`bend` at zero the tube is straight, otherwise the curve is pulled towards the middle
+//point of the tube moved by `bend`
+pub struct PushrodTube
+{
+    pub start: na::Vector3<f32>,
+    pub end: na::Vector3<f32>,
+    pub bend: na::Vector3<f32>,
+
+    pub outer_diameter: f32,
+    pub inner_diameter: f32,
+
+    //Straight pieces the curve is split into
+    pub segments: usize,
+}
+
+//How far the bore continues past the end of the tube to make sure it exits the shell
+const BORE_EXTENSION: f32 = 10.0;
+
+impl PushrodTube
+{
+    fn point(&self, t: f32) -> na::Vector3<f32>
+    {
+        let control = (self.start + self.end) / 2.0 + self.bend;
+
+        self.start * ((1.0 - t) * (1.0 - t))
+            + control * (2.0 * (1.0 - t) * t)
+            + self.end * (t * t)
+    }
+
+    pub fn points(&self) -> Vec<na::Vector3<f32>>
+    {
+        (0..self.segments + 1)
+            .map(|i| self.point(i as f32 / self.segments as f32))
+            .collect()
+    }
+
+    //A chain of spheres hulled together along the curve
+    fn tube(points: &[na::Vector3<f32>], diameter: f32) -> ScadObject
+    {
+        let mut result = scad!(Union);
+
+        for pair in points.windows(2)
+        {
+            result.add_child(scad!(Hull;
+            {
+                scad!(Translate(pair[0]); scad!(Sphere(Diameter(diameter)))),
+                scad!(Translate(pair[1]); scad!(Sphere(Diameter(diameter))))
+            }));
+        }
+        result
+    }
+
+    fn wall(&self) -> ScadObject
+    {
+        PushrodTube::tube(&self.points(), self.outer_diameter)
+    }
+
+    //Where the bore ends after continuing past the end of the tube
+    pub fn bore_end(&self) -> na::Vector3<f32>
+    {
+        let before_end = self.point(1.0 - 1.0 / self.segments as f32);
+
+        self.end + (self.end - before_end).normalize() * BORE_EXTENSION
+    }
+
+    fn bore(&self) -> ScadObject
+    {
+        let mut points = self.points();
+        points.push(self.bore_end());
+
+        PushrodTube::tube(&points, self.inner_diameter)
+    }
+}
+
+pub struct FuselageInterior
+{
+    pub servos: Vec<ServoPlacement>,
+    pub pushrods: Vec<PushrodTube>,
+}
+
+impl FuselageInterior
+{
+    //Adds the internal features to the fuselage shell. `outer_shape` is the solid outside of
+    //the fuselage which keeps the bosses and tubes from sticking out of it
+    pub fn apply(&self, shell: ScadObject, outer_shape: ScadObject) -> ScadObject
+    {
+        let mut additions = scad!(Union);
+        let mut cuts = scad!(Union);
+
+        for servo in &self.servos
+        {
+            additions.add_child(servo.bosses());
+            cuts.add_child(servo.pocket());
+        }
+
+        for pushrod in &self.pushrods
+        {
+            additions.add_child(pushrod.wall());
+            cuts.add_child(pushrod.bore());
+        }
+
+        scad!(Difference;
+        {
+            scad!(Union;
+            {
+                shell,
+                scad!(Intersection;{additions, outer_shape}),
+            }),
+            cuts
+        })
+    }
+}
@@ -0,0 +1,183 @@
+use std::fmt;
+
+use na;
+use scad_generator::*;
+
+use rc_common::servo::ServoSpec;
+
+//A servo arm driving a control horn through a straight pushrod. Positions are in the
+//coordinates of the plane. Throws are in degrees, up is the trailing edge going up.
+pub struct Linkage
+{
+    pub servo: ServoSpec,
+    pub shaft_position: na::Vector3<f32>,
+    pub shaft_axis: na::Vector3<f32>,
+    //Distance from the shaft to the pushrod hole in the servo arm
+    pub arm_length: f32,
+
+    //Point on the hinge line where the horn is and the direction the horn points in
+    pub hinge_point: na::Vector3<f32>,
+    pub horn_direction: na::Vector3<f32>,
+
+    pub up_throw: f32,
+    pub down_throw: f32,
+}
+
+pub struct LinkageGeometry
+{
+    pub horn_length: f32,
+    //Angle of the servo arm at neutral away from square to the pushrod, towards the
+    //direction that gives the up throw. Non zero for differential throws
+    pub arm_angle: f32,
+    pub pushrod_length: f32,
+    //Horn length over servo arm length
+    pub mechanical_advantage: f32,
+    //Largest torque at the hinge line in Nmm
+    pub hinge_torque: f32,
+}
+
+impl Linkage
+{
+    //The arm angle that gives the ratio between the up and down throw. Moving the arm
+    //by the servo travel from an angle `a` moves the pushrod proportional to
+    //cos(a + travel/2) one way and cos(a - travel/2) the other way
+    fn arm_angle(&self) -> f32
+    {
+        let ratio = self.up_throw.to_radians().sin() / self.down_throw.to_radians().sin();
+        let half_travel = (self.servo.travel / 2.0).to_radians();
+
+        ((1.0 - ratio) / (1.0 + ratio) / half_travel.tan()).atan()
+    }
+
+    //Pushrod movement for the servo going fully to the up and the down side
+    fn pushrod_travel(&self, arm_angle: f32) -> (f32, f32)
+    {
+        let travel = self.servo.travel.to_radians();
+
+        (
+            self.arm_length * ((arm_angle + travel).sin() - arm_angle.sin()),
+            self.arm_length * (arm_angle.sin() - (arm_angle - travel).sin())
+        )
+    }
+
+    pub fn geometry(&self) -> LinkageGeometry
+    {
+        let arm_angle = self.arm_angle();
+        let (up_travel, _) = self.pushrod_travel(arm_angle);
+
+        let horn_length = up_travel / self.up_throw.to_radians().sin();
+        let horn_hole = self.hinge_point + self.horn_direction.normalize() * horn_length;
+
+        //The arm is square to the pushrod at zero arm angle
+        let along = (horn_hole - self.shaft_position).normalize();
+        let across = self.shaft_axis.cross(&along).normalize();
+        let arm_hole = self.shaft_position
+            + (across * arm_angle.cos() + along * arm_angle.sin()) * self.arm_length;
+
+        let mechanical_advantage = horn_length / self.arm_length;
+
+        LinkageGeometry {
+            horn_length,
+            arm_angle: arm_angle.to_degrees(),
+            pushrod_length: (horn_hole - arm_hole).norm(),
+            mechanical_advantage,
+            hinge_torque: self.servo.torque * mechanical_advantage,
+        }
+    }
+
+    //A horn matching the linkage for a surface that is `surface_thickness` thick at the hinge
+    pub fn horn(&self, surface_thickness: f32) -> ControlHorn
+    {
+        ControlHorn {
+            horn_length: self.geometry().horn_length,
+            surface_thickness,
+            hole_diameter: 1.2,
+            thickness: 1.5,
+            wall: 1.5,
+            base_length: 15.0,
+            base_width: 6.0,
+            base_thickness: 1.0,
+            hinge_gap: 1.0,
+        }
+    }
+}
+
+impl fmt::Display for LinkageGeometry
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        writeln!(f, "Horn length: {:.1} mm", self.horn_length)?;
+        writeln!(f, "Servo arm angle at neutral: {:.1} degrees", self.arm_angle)?;
+        writeln!(f, "Pushrod length: {:.1} mm", self.pushrod_length)?;
+        writeln!(f, "Mechanical advantage: {:.2}", self.mechanical_advantage)?;
+        writeln!(f, "Torque at the hinge: {:.0} Nmm", self.hinge_torque)
+    }
+}
+
+//A horn glued on top of a control surface. The hinge line is at the origin going along
+//x, the chord goes along z and the pushrod hole is `horn_length` above the hinge line.
+pub struct ControlHorn
+{
+    pub horn_length: f32,
+    pub surface_thickness: f32,
+
+    pub hole_diameter: f32,
+    pub thickness: f32,
+    //Material around the hole
+    pub wall: f32,
+
+    //The base is glued to the surface behind the hinge line
+    pub base_length: f32,
+    pub base_width: f32,
+    pub base_thickness: f32,
+    //Distance between the hinge line and the front of the base
+    pub hinge_gap: f32,
+}
+
+impl ControlHorn
+{
+    //Cylinder along x centered on the horn
+    fn cross_cylinder(&self, diameter: f32) -> ScadObject
+    {
+        scad!(Translate(vec3(-self.thickness, self.horn_length, 0.0));
+        {
+            scad!(Rotate(90.0, vec3(0.0, 1.0, 0.0));
+            {
+                scad!(Cylinder(self.thickness * 2.0, Diameter(diameter)))
+            })
+        })
+    }
+
+    pub fn get(&self) -> ScadObject
+    {
+        let surface = self.surface_thickness / 2.0;
+
+        let base = scad!(Translate(vec3(-self.base_width / 2.0, surface, self.hinge_gap));
+        {
+            scad!(Cube(vec3(self.base_width, self.base_thickness, self.base_length)))
+        });
+
+        //A triangle from the base up to the hole
+        let arm = scad!(Intersection;
+        {
+            scad!(Hull;
+            {
+                scad!(Translate(vec3(-self.thickness / 2.0, surface, self.hinge_gap));
+                {
+                    scad!(Cube(vec3(self.thickness, self.base_thickness, self.base_length)))
+                }),
+                self.cross_cylinder(self.hole_diameter + self.wall * 2.0)
+            }),
+            scad!(Translate(vec3(-self.thickness / 2.0, surface, -self.horn_length));
+            {
+                scad!(Cube(vec3(self.thickness, self.horn_length * 2.0, self.horn_length * 2.0 + self.base_length)))
+            })
+        });
+
+        scad!(Difference;
+        {
+            scad!(Union;{base, arm}),
+            self.cross_cylinder(self.hole_diameter)
+        })
+    }
+}
@@ -0,0 +1,394 @@
+use na;
+use scad_generator::*;
+
+//A cross section of a loft at `station` along z. The outline is the superellipse
+//|x/(width/2)|^exponent + |y/(height/2)|^exponent = 1 moved up by `vertical_offset`.
+//An exponent of 2 gives an ellipse and larger exponents get closer to a rectangle.
+#[derive(Clone, Copy, Debug)]
+pub struct Section
+{
+    pub station: f32,
+    pub width: f32,
+    pub height: f32,
+    pub exponent: f32,
+    pub vertical_offset: f32,
+}
+
+impl Section
+{
+    //The values that are interpolated between sections
+    fn values(&self) -> [f32; 4]
+    {
+        [self.width, self.height, self.exponent, self.vertical_offset]
+    }
+
+    fn from_values(station: f32, values: [f32; 4]) -> Section
+    {
+        Section {
+            station,
+            width: values[0],
+            height: values[1],
+            exponent: values[2],
+            vertical_offset: values[3],
+        }
+    }
+
+    //Distance from the center of the section to the outline in the direction `angle`
+    pub fn radius_at(&self, angle: f32) -> f32
+    {
+        let a = self.width / 2.0;
+        let b = self.height / 2.0;
+
+        let sum = (angle.cos() / a).abs().powf(self.exponent)
+            + (angle.sin() / b).abs().powf(self.exponent);
+
+        1.0 / sum.powf(1.0 / self.exponent)
+    }
+
+    //Half the width of the outline at height `y`, None above or below the section
+    pub fn half_width_at(&self, y: f32) -> Option<f32>
+    {
+        let relative = ((y - self.vertical_offset) / (self.height / 2.0)).abs();
+
+        if relative > 1.0
+        {
+            None
+        }
+        else
+        {
+            Some(self.width / 2.0 * (1.0 - relative.powf(self.exponent)).powf(1.0 / self.exponent))
+        }
+    }
+
+    fn outline(&self, points: usize) -> Vec<na::Vector3<f32>>
+    {
+        let power = 2.0 / self.exponent;
+
+        (0..points)
+            .map(|i| {
+                let angle = i as f32 / points as f32 * 2.0 * std::f32::consts::PI;
+                let (sin, cos) = angle.sin_cos();
+
+                vec3(
+                    self.width / 2.0 * cos.signum() * cos.abs().powf(power),
+                    self.height / 2.0 * sin.signum() * sin.abs().powf(power) + self.vertical_offset,
+                    self.station
+                )
+            })
+            .collect()
+    }
+}
+
+//How the sections between the given sections are computed
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Interpolation
+{
+    //Straight lines between the sections, gives kinks at every section
+    Linear,
+    //Smooth curve through the sections which can overshoot between them
+    CatmullRom,
+    //Smooth curve which never goes outside the range of the neighbouring sections
+    MonotoneCubic,
+}
+
+//Slopes of a cubic hermite spline through (xs, ys) at each point
+fn spline_tangents(xs: &[f32], ys: &[f32], interpolation: Interpolation) -> Vec<f32>
+{
+    let count = xs.len();
+    let secants = (0..count - 1)
+        .map(|i| (ys[i + 1] - ys[i]) / (xs[i + 1] - xs[i]))
+        .collect::<Vec<_>>();
+
+    let mut tangents = (0..count)
+        .map(|i| {
+            if i == 0
+            {
+                secants[0]
+            }
+            else if i == count - 1
+            {
+                secants[count - 2]
+            }
+            else
+            {
+                match interpolation
+                {
+                    Interpolation::Linear => 0.0,
+                    Interpolation::CatmullRom => (ys[i + 1] - ys[i - 1]) / (xs[i + 1] - xs[i - 1]),
+                    Interpolation::MonotoneCubic => {
+                        if secants[i - 1] * secants[i] <= 0.0
+                        {
+                            0.0
+                        }
+                        else
+                        {
+                            (secants[i - 1] + secants[i]) / 2.0
+                        }
+                    }
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    //Fritsch-Carlson limiting of the slopes to keep the curve monotone between points
+    if interpolation == Interpolation::MonotoneCubic
+    {
+        for i in 0..count - 1
+        {
+            if secants[i] == 0.0
+            {
+                tangents[i] = 0.0;
+                tangents[i + 1] = 0.0;
+                continue;
+            }
+
+            let alpha = tangents[i] / secants[i];
+            let beta = tangents[i + 1] / secants[i];
+            let length = (alpha * alpha + beta * beta).sqrt();
+
+            if length > 3.0
+            {
+                tangents[i] = 3.0 / length * alpha * secants[i];
+                tangents[i + 1] = 3.0 / length * beta * secants[i];
+            }
+        }
+    }
+
+    tangents
+}
+
+//Zero sized sections give degenerate faces so they are clamped to this size
+const MIN_SECTION_SIZE: f32 = 0.01;
+
+pub struct Loft
+{
+    //Sections ordered by station
+    pub sections: Vec<Section>,
+    //Extra sections generated between each pair of sections
+    pub interpolated_stations: usize,
+    pub interpolation: Interpolation,
+    //Points around each section
+    pub outline_points: usize,
+}
+
+impl Loft
+{
+    pub fn new(sections: Vec<Section>, interpolated_stations: usize, interpolation: Interpolation) -> Loft
+    {
+        Loft {
+            sections,
+            interpolated_stations,
+            interpolation,
+            outline_points: 48,
+        }
+    }
+
+    fn with_sections<F>(&self, transform: F) -> Loft
+        where F: Fn(&Section) -> Section
+    {
+        Loft {
+            sections: self.sections.iter().map(transform).collect(),
+            interpolated_stations: self.interpolated_stations,
+            interpolation: self.interpolation,
+            outline_points: self.outline_points,
+        }
+    }
+
+    //The same loft scaled around the origin
+    pub fn scaled(&self, scale: f32) -> Loft
+    {
+        self.with_sections(|section| Section {
+            station: section.station * scale,
+            width: section.width * scale,
+            height: section.height * scale,
+            vertical_offset: section.vertical_offset * scale,
+            ..*section
+        })
+    }
+
+    //The same loft with every section made `distance` smaller on all sides
+    pub fn inset(&self, distance: f32) -> Loft
+    {
+        self.with_sections(|section| Section {
+            width: section.width - distance * 2.0,
+            height: section.height - distance * 2.0,
+            ..*section
+        })
+    }
+
+    //Tangents of each interpolated value at each section
+    fn tangents(&self) -> Vec<[f32; 4]>
+    {
+        let stations = self.sections.iter().map(|s| s.station).collect::<Vec<_>>();
+
+        let mut result = vec!([0.0; 4]; self.sections.len());
+        for value in 0..4
+        {
+            let ys = self.sections.iter().map(|s| s.values()[value]).collect::<Vec<_>>();
+
+            for (i, tangent) in spline_tangents(&stations, &ys, self.interpolation).into_iter().enumerate()
+            {
+                result[i][value] = tangent;
+            }
+        }
+        result
+    }
+
+    fn interpolate(&self, tangents: &[[f32; 4]], index: usize, station: f32) -> Section
+    {
+        let (start, end) = (&self.sections[index], &self.sections[index + 1]);
+
+        let h = end.station - start.station;
+        let t = (station - start.station) / h;
+
+        //Cubic hermite basis functions
+        let h00 = 2.0 * t * t * t - 3.0 * t * t + 1.0;
+        let h10 = t * t * t - 2.0 * t * t + t;
+        let h01 = -2.0 * t * t * t + 3.0 * t * t;
+        let h11 = t * t * t - t * t;
+
+        let (start_values, end_values) = (start.values(), end.values());
+
+        let mut values = [0.0; 4];
+        for i in 0..4
+        {
+            values[i] = if self.interpolation == Interpolation::Linear
+            {
+                start_values[i] + (end_values[i] - start_values[i]) * t
+            }
+            else
+            {
+                h00 * start_values[i]
+                    + h10 * h * tangents[index][i]
+                    + h01 * end_values[i]
+                    + h11 * h * tangents[index + 1][i]
+            };
+        }
+
+        Section::from_values(station, values)
+    }
+
+    //The section at `station`, None outside the loft
+    pub fn section_at(&self, station: f32) -> Option<Section>
+    {
+        let tangents = self.tangents();
+
+        self.sections.windows(2)
+            .position(|pair| station >= pair[0].station && station <= pair[1].station)
+            .map(|index| self.interpolate(&tangents, index, station))
+    }
+
+    //True if `point` is inside the outline of the section at its station
+    pub fn contains(&self, point: na::Vector3<f32>) -> bool
+    {
+        match self.section_at(point.z)
+        {
+            Some(section) => {
+                let (x, y) = (point.x, point.y - section.vertical_offset);
+                (x * x + y * y).sqrt() < section.radius_at(y.atan2(x))
+            }
+            None => false,
+        }
+    }
+
+    fn all_sections(&self) -> Vec<Section>
+    {
+        let tangents = self.tangents();
+        let steps = self.interpolated_stations + 1;
+
+        let mut result = vec!();
+        for index in 0..self.sections.len() - 1
+        {
+            let (start, end) = (self.sections[index].station, self.sections[index + 1].station);
+
+            for i in 0..steps
+            {
+                let station = start + (end - start) * i as f32 / steps as f32;
+                result.push(self.interpolate(&tangents, index, station));
+            }
+        }
+        result.extend(self.sections.last());
+
+        result
+    }
+
+    pub fn get(&self) -> ScadObject
+    {
+        let sections = self.all_sections();
+        let count = self.outline_points;
+
+        let mut points = vec!();
+        for section in &sections
+        {
+            let clamped = Section {
+                width: section.width.max(MIN_SECTION_SIZE),
+                height: section.height.max(MIN_SECTION_SIZE),
+                ..*section
+            };
+            points.extend(clamped.outline(count));
+        }
+
+        let index = |section: usize, point: usize| (section * count + point % count) as i32;
+
+        //Faces are listed clockwise when seen from the outside
+        let mut faces = vec!();
+        faces.push((0..count).map(|i| index(0, i)).collect());
+        for s in 0..sections.len() - 1
+        {
+            for i in 0..count
+            {
+                faces.push(vec!(index(s, i), index(s + 1, i), index(s + 1, i + 1), index(s, i + 1)));
+            }
+        }
+        faces.push((0..count).rev().map(|i| index(sections.len() - 1, i)).collect());
+
+        scad!(Polyhedron(points, faces))
+    }
+}
+
+//Twice the signed area of a profile, positive when it goes counterclockwise
+fn profile_area(profile: &[(f32, f32)]) -> f32
+{
+    (0..profile.len())
+        .map(|i| {
+            let (a, b) = (profile[i], profile[(i + 1) % profile.len()]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum()
+}
+
+//Extrudes a profile in the fuselage side plane, where the first coordinate is z and the
+//second is y, `width` along x centered on the fuselage axis
+pub fn extrude_profile(profile: &[(f32, f32)], width: f32) -> ScadObject
+{
+    let mut profile = profile.to_vec();
+    if profile_area(&profile) < 0.0
+    {
+        profile.reverse();
+    }
+
+    let count = profile.len();
+
+    let mut points = vec!();
+    for x in &[-width / 2.0, width / 2.0]
+    {
+        for &(z, y) in &profile
+        {
+            points.push(vec3(*x, y, z));
+        }
+    }
+
+    let index = |side: usize, point: usize| (side * count + point % count) as i32;
+
+    //Faces are listed clockwise when seen from the outside
+    let mut faces = vec!();
+    faces.push((0..count).rev().map(|i| index(0, i)).collect());
+    faces.push((0..count).map(|i| index(1, i)).collect());
+    for i in 0..count
+    {
+        faces.push(vec!(index(0, i), index(0, i + 1), index(1, i + 1), index(1, i)));
+    }
+
+    scad!(Polyhedron(points, faces))
+}
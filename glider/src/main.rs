@@ -1,6 +1,8 @@
 //"Import" the module along with the macros
 #[macro_use]
 extern crate scad_generator;
+extern crate nalgebra as na;
+extern crate rc_common;
 
 //Avoid having to write scad_generator:: everywhere
 use scad_generator::*;
@@ -9,96 +11,198 @@ use std::io::prelude::*;
 use std::fs::File;
 use std::path::Path;
 
-fn cut_cone(d1: f32, d2: f32, height: f32) -> ScadObject
-{
-    let cyl_height = 0.0000001;
-    scad!(Hull; 
-        {
-            scad!(Cylinder(cyl_height, Diameter(d1))),
+mod interior;
+mod loft;
+mod hatch;
+mod ballast;
+mod airfoil;
+mod wing_mount;
+mod tail;
+mod hinge;
+mod linkage;
 
-            scad!(Translate(vec3(0.0, 0.0, height));{
-                scad!(Cylinder(cyl_height, Diameter(d2)))
-            }),
-        })
+use rc_common::servo::ServoSpec;
+use rc_common::assembly::{Assembly, PlacedPart};
+use rc_common::interference;
+use interior::{FuselageInterior, PushrodTube, ServoPlacement};
+use loft::{Interpolation, Loft, Section};
+use hatch::Hatch;
+use ballast::{BallastChamber, BallastMaterial, TowHook};
+use airfoil::Naca4;
+use wing_mount::{JoinerRod, WingMount, WingRetention};
+use tail::{Empennage, TailBoom, TailSurface, TailType};
+use hinge::{Hinge, HingeType};
+use linkage::Linkage;
+
+//Cross sections of the fuselage from the nose to the tail
+const BODY_SECTIONS: [Section; 6] = [
+        Section {station: 0.0, width: 25.0, height: 25.0, exponent: 2.0, vertical_offset: 0.0},
+        Section {station: 20.0, width: 34.0, height: 34.0, exponent: 2.0, vertical_offset: 0.0},
+        Section {station: 60.0, width: 40.0, height: 40.0, exponent: 2.0, vertical_offset: 0.0},
+        Section {station: 140.0, width: 45.0, height: 45.0, exponent: 2.0, vertical_offset: 0.0},
+        Section {station: 220.0, width: 40.0, height: 40.0, exponent: 2.0, vertical_offset: 0.0},
+        Section {station: 360.0, width: 10.0, height: 10.0, exponent: 2.0, vertical_offset: 0.0},
+    ];
+
+//Sections generated between each pair in `BODY_SECTIONS`
+const BODY_INTERPOLATED_STATIONS: usize = 8;
+
+//Position of the center of gravity along the fuselage
+const CG_STATION: f32 = 135.0;
+
+//The scale of the inside of the fuselage shell
+const BODY_INNER_SCALE: f32 = 0.9;
+
+//Size of the blocks used to cut shapes apart, large enough to go through the whole
+//fuselage and any tail surface
+const CUTTER_SIZE: f32 = 400.0;
+
+fn body_loft() -> Loft
+{
+    Loft::new(BODY_SECTIONS.to_vec(), BODY_INTERPOLATED_STATIONS, Interpolation::MonotoneCubic)
 }
 
-fn servo() -> ScadObject
+fn glider_body_shape() -> ScadObject
 {
-    let box_size = vec3(24.0, 13.0, 22.0);
-    let screw_diameter = 3.0;
-    let screw_mount_size = vec3(33.0, box_size.y, 2.5);
-    let screw_height = 10.0;
-    let screw_edge_offset = 2.0;
+    body_loft().get()
+}
 
-    let mount_offset = vec3(0.0, 0.0, 16.0);
+//The inside of the shell is the outer shape scaled around the origin
+fn body_inner_loft() -> Loft
+{
+    body_loft().scaled(BODY_INNER_SCALE)
+}
 
+fn body_inner_section(z: f32) -> Option<Section>
+{
+    body_inner_loft().section_at(z)
+}
 
-    let mut base_cube = scad!(
-        Translate(-vec3(box_size.x / 2.0, 0.0, 0.0));
+//Smallest distance between a placed part and the inner wall of the fuselage, measured
+//from the center of each section. Negative values mean that the part goes through the wall.
+fn wall_clearance(placed: &PlacedPart) -> f32
+{
+    interference::surface_points(placed).iter()
+        .map(|point| match body_inner_section(point.z)
         {
-            scad!(Cube(box_size))
-        });
-
-    //Creating the screws for the servo
-    let mut screws = {
-        let mut result = scad!(Translate(vec3(0.0, box_size.y/2.0, mount_offset.z - screw_height / 2.0)));
+            Some(section) => {
+                let y = point.y - section.vertical_offset;
+                section.radius_at(y.atan2(point.x)) - (point.x * point.x + y * y).sqrt()
+            }
+            None => -(point.x * point.x + point.y * point.y).sqrt(),
+        })
+        .fold(std::f32::MAX, f32::min)
+}
 
-        for i in &[-1.0,1.0]
-        {
-            let x_offset = screw_mount_size.x / 2.0 - screw_edge_offset;
+//Smallest gap between the pushrods and the underside of the wing saddle floor
+const MIN_SADDLE_CLEARANCE: f32 = 1.0;
 
-            let mut screw = 
-            scad!(
-                Translate(vec3(i * x_offset, 0.0, 0.0));
-                scad!(Cylinder(screw_height, Diameter(screw_diameter)))
-            );
+//Steps each pushrod segment is split into when it is checked against the saddle floor
+const SADDLE_CHECK_STEPS: usize = 20;
 
-            result.add_child(screw);
-        }
-        result
-    };
+//Smallest distance between the top of a pushrod tube and the underside of the saddle
+//floor. The floor goes across the whole fuselage so only the side plane matters, and the
+//rod sockets are above it.
+fn saddle_clearance(mount: &WingMount, pushrod: &PushrodTube) -> f32
+{
+    let points = pushrod.points();
 
-    let screw_mount = scad!(
-        Translate(
-                -vec3(screw_mount_size.x / 2.0, 0.0, 0.0) +
-                mount_offset
-            );
+    let mut result = std::f32::MAX;
+    for pair in points.windows(2)
+    {
+        for i in 0..SADDLE_CHECK_STEPS + 1
         {
-            scad!(Cube(screw_mount_size)),
-        });
+            let point = pair[0] + (pair[1] - pair[0]) * (i as f32 / SADDLE_CHECK_STEPS as f32);
 
-    scad!(Union;{base_cube, screw_mount, screws})
+            if let Some(bottom) = mount.floor_bottom(point.z)
+            {
+                result = result.min(bottom - point.y - pushrod.outer_diameter / 2.0);
+            }
+        }
+    }
+    result
 }
 
-fn glider_body_shape() -> ScadObject
+//Checks the servos of `glider_interior` against each other, the hatch and the fuselage wall
+fn check_interior()
 {
-    let segments = [
-            (25.0, 34.0, 20.0),
-            (34.0, 40.0, 40.0),
-            (40.0, 45.0, 80.0),
-            (45.0, 40.0, 80.0),
-            (40.0, 10.0, 140.0),
-        ];
+    let interior = glider_interior();
 
-    let mut result = scad!(Union);
+    let names = (0..interior.servos.len())
+        .map(|i| format!("servo_{}", i + 1))
+        .collect::<Vec<_>>();
 
-    let mut current_height = 0.0;
-    for i in 0..segments.len()
+    let mut assembly = Assembly::new(interior.servos[0].part(&names[0]));
+    for (placement, name) in interior.servos.iter().zip(names.iter()).skip(1)
     {
-        result.add_child(scad!(Translate(vec3(0.0, 0.0, current_height));
-            {
-                cut_cone(segments[i].0, segments[i].1, segments[i].2),
-            }));
+        assembly = assembly.with_part(placement.part(name));
+    }
 
-        current_height += segments[i].2;
+    //The hatch lip and magnet bosses are added to the shell after the servo pockets are cut
+    let hatch = glider_hatch();
+    assembly = assembly
+        .with_part(hatch.lip_part(&body_inner_loft()))
+        .with_part(hatch.boss_part(&body_loft(), &body_inner_loft()));
 
+    //The magnet bosses hang on to the lip
+    for interference in interference::find_interferences(&assembly)
+    {
+        if !(interference.first.starts_with("hatch") && interference.second.starts_with("hatch"))
+        {
+            println!("{}", interference);
+        }
+    }
+    for hatch_part in &["hatch_lip", "hatch_bosses"]
+    {
+        for name in &names
+        {
+            match interference::clearance(&assembly, hatch_part, name)
+            {
+                Some(clearance) => {
+                    println!("Clearance between {} and {}: {:.2} mm", hatch_part, name, clearance);
+                    assert!(clearance > 0.0, "The {} go into {}", hatch_part, name);
+                }
+                None => panic!("{} or {} has no collision volumes", hatch_part, name),
+            }
+        }
+    }
+    for i in 1..names.len()
+    {
+        match interference::clearance(&assembly, &names[i - 1], &names[i])
+        {
+            Some(clearance) => println!("Clearance between {} and {}: {:.2} mm", names[i - 1], names[i], clearance),
+            None => panic!("{} or {} has no collision volumes", names[i - 1], names[i]),
+        }
+    }
+    for name in &names
+    {
+        println!("{} clearance to the fuselage wall: {:.2} mm", name, wall_clearance(assembly.find(name)));
     }
 
-    result
+    //The pushrods have to be connected inside the cavity and come out through the shell
+    let (outer, inner) = (body_loft(), body_inner_loft());
+    for (i, pushrod) in interior.pushrods.iter().enumerate()
+    {
+        assert!(inner.contains(pushrod.start), "Pushrod {} starts outside the fuselage cavity", i + 1);
+        assert!(
+            !outer.inset(-pushrod.inner_diameter / 2.0).contains(pushrod.bore_end()),
+            "Pushrod {} bore ends inside the fuselage shell",
+            i + 1
+        );
+    }
+
+    let mount = glider_wing_mount();
+    for (i, pushrod) in interior.pushrods.iter().enumerate()
+    {
+        let clearance = saddle_clearance(&mount, pushrod);
+        println!("Pushrod {} clearance to the wing saddle floor: {:.2} mm", i + 1, clearance);
+        assert!(clearance >= MIN_SADDLE_CLEARANCE, "Pushrod {} is too close to the wing saddle floor", i + 1);
+    }
 }
+
 fn glider_body() -> ScadObject
 {
-    let inner_scale = 0.9;
+    let inner_scale = BODY_INNER_SCALE;
 
     scad!(Difference;
     {
@@ -110,7 +214,204 @@ fn glider_body() -> ScadObject
     })
 }
 
+//Elevator and rudder servos side by side under the hatch with pushrods running under
+//the wing saddle to the tail
+fn glider_interior() -> FuselageInterior
+{
+    let servo = ServoSpec::micro_9g();
+    //Far enough forward for the tabs to stay clear of the rear hatch magnets
+    let station = 58.0;
+    let arm_length = 5.0;
+    let tail_exit = 340.0;
 
+    let mut servos = vec!();
+    let mut pushrods = vec!();
+    for side in &[-1.0, 1.0]
+    {
+        let placement = ServoPlacement {
+            servo,
+            station,
+            side_offset: side * (servo.body_size.y / 2.0 + 0.5),
+            height_offset: -servo.body_size.z / 2.0,
+            //The arms point inwards so the pushrods run down the middle of the fuselage
+            arm_length: -side * arm_length,
+        };
+
+        pushrods.push(PushrodTube {
+            start: placement.horn_position(),
+            //Out through the side of the tail beside the boom socket. The tube is cut off
+            //at the outer surface
+            end: vec3(side * 8.5, 1.0, tail_exit),
+            bend: vec3(0.0, 0.0, 0.0),
+            outer_diameter: 4.0,
+            inner_diameter: 2.2,
+            segments: 8,
+        });
+        servos.push(placement);
+    }
+
+    FuselageInterior {
+        servos,
+        pushrods,
+    }
+}
+
+//Room between the servos and the ends of the gaps in the hatch lip above them
+const LIP_GAP_CLEARANCE: f32 = 1.0;
+
+//Hatch over the battery, receiver and servos in front of the wing. The lip is left out
+//above the servos so they can be put in from above
+fn glider_hatch() -> Hatch
+{
+    Hatch {
+        start: 20.0,
+        end: 88.0,
+        cut_height: 8.0,
+        lip_width: 3.0,
+        lip_depth: 6.0,
+        lip_gaps: glider_interior().servos.iter()
+            .map(|servo| servo.station_range(LIP_GAP_CLEARANCE))
+            .collect(),
+        clearance: 0.3,
+        magnet_stations: vec!(28.0, 80.0),
+        magnet_diameter: 5.0,
+        magnet_height: 2.0,
+        magnet_wall: 1.5,
+    }
+}
+
+fn glider_ballast() -> BallastChamber
+{
+    BallastChamber {
+        mass: 50.0,
+        material: BallastMaterial::Lead,
+        diameter: 12.0,
+        wall_thickness: 1.5,
+        cg_station: CG_STATION,
+        height_offset: -8.0,
+        //M8 nylon screw
+        fill_diameter: 6.8,
+    }
+}
+
+fn glider_tow_hook() -> TowHook
+{
+    TowHook {
+        cg_station: CG_STATION,
+        angle: 10.0,
+        pad_width: 12.0,
+        pad_length: 20.0,
+        pad_thickness: 5.0,
+        screw_diameter: 2.5,
+    }
+}
+
+//Root chord of the wing
+const WING_CHORD: f32 = 140.0;
+
+//The CG is at a third of the root chord
+fn glider_wing_mount() -> WingMount
+{
+    WingMount {
+        airfoil: Naca4::new("2412"),
+        chord: WING_CHORD,
+        leading_edge: CG_STATION - WING_CHORD * 0.33,
+        height: 25.0,
+        incidence: 2.0,
+        center_width: 16.0,
+        saddle_width: 36.0,
+        wall_thickness: 1.5,
+        rods: vec!(
+            JoinerRod {chord_position: 0.25, diameter: 5.0},
+            JoinerRod {chord_position: 0.65, diameter: 3.0},
+        ),
+        rod_clearance: 0.2,
+        retention: WingRetention::Bolt {diameter: 3.0, chord_position: 0.45},
+    }
+}
+
+fn glider_tail_boom() -> TailBoom
+{
+    TailBoom {
+        diameter: 6.0,
+        clearance: 0.2,
+        length: 450.0,
+        socket_depth: 30.0,
+        wall_thickness: 1.5,
+    }
+}
+
+fn glider_empennage() -> Empennage
+{
+    let stabiliser = TailSurface {
+        root_chord: 70.0,
+        tip_chord: 50.0,
+        span: 140.0,
+        sweep: 10.0,
+        thickness: 0.08,
+        hinge_position: 0.7,
+        hinge_gap: 0.5,
+        root_offset: 6.0,
+    };
+
+    let fin = TailSurface {
+        root_chord: 80.0,
+        tip_chord: 45.0,
+        span: 110.0,
+        sweep: 25.0,
+        thickness: 0.08,
+        hinge_position: 0.65,
+        hinge_gap: 0.5,
+        root_offset: 6.0,
+    };
+
+    Empennage {
+        tail_type: TailType::Conventional {stabiliser, fin},
+        sleeve_length: 40.0,
+        sleeve_wall: 1.5,
+        //1 mm steel wire as hinge pins, the knuckles are too thin at the tips for filament
+        hinge: Hinge {
+            hinge_type: HingeType::Pin {pin_diameter: 1.0, knuckles: 7},
+            clearance: 0.2,
+            max_up: 30.0,
+            max_down: 30.0,
+        },
+    }
+}
+
+fn glider_stabiliser() -> TailSurface
+{
+    match glider_empennage().tail_type
+    {
+        TailType::Conventional {stabiliser, ..} => stabiliser,
+        TailType::VTail {surface, ..} => surface,
+    }
+}
+
+//Distance from the root of the elevator to the horn
+const ELEVATOR_HORN_OFFSET: f32 = 3.0;
+
+//The left servo drives the elevator through a horn on the top of the right elevator,
+//just outside the root offset
+fn glider_elevator_linkage() -> Linkage
+{
+    let servo = &glider_interior().servos[0];
+
+    let stabiliser = glider_stabiliser();
+    let (hinge_start, _) = stabiliser.hinge_line();
+    let boom_end = glider_tail_boom().end(&body_loft());
+
+    Linkage {
+        servo: servo.servo,
+        shaft_position: servo.shaft_position(),
+        shaft_axis: vec3(0.0, 1.0, 0.0),
+        arm_length: servo.arm_length.abs(),
+        hinge_point: vec3(hinge_start.x + ELEVATOR_HORN_OFFSET, 0.0, boom_end - stabiliser.root_chord + hinge_start.z),
+        horn_direction: vec3(0.0, 1.0, 0.0),
+        up_throw: 15.0,
+        down_throw: 12.0,
+    }
+}
 
 pub fn main()
 {
@@ -129,11 +430,54 @@ pub fn main()
     let mut sfile = ScadFile::new();
 
     sfile.set_detail(50);
-    sfile.add_object(servo());
+    sfile.add_object(ServoSpec::micro_9g().keep_out(0.0));
     sfile.write_to_file(String::from("glider_auto.scad"));
 
+    let mut body_file = ScadFile::new();
+    body_file.set_detail(50);
+    let hatch = glider_hatch();
+    let ballast = glider_ballast();
+    println!("Ballast tube length: {:.1} mm, filled from below at {:.0} mm", ballast.length(), ballast.fill_station());
+
+    let shell = glider_interior().apply(glider_body(), glider_body_shape());
+    let shell = ballast.apply(shell, glider_body_shape());
+    let shell = glider_tow_hook().apply(shell, &body_loft());
+    let shell = glider_tail_boom().apply(shell, &body_loft());
+    let shell = hatch.body(shell, &body_loft(), &body_inner_loft());
+    body_file.add_object(glider_wing_mount().apply(shell, &body_loft()));
+    body_file.write_to_file(String::from("glider_body_auto.scad"));
+
+    let mut lid_file = ScadFile::new();
+    lid_file.set_detail(50);
+    //The internal parts stay in the body so the lid is cut from the bare shell
+    lid_file.add_object(hatch.lid(glider_body(), &body_loft(), &body_inner_loft()));
+    lid_file.write_to_file(String::from("glider_lid_auto.scad"));
+
+    let (horizontal_area, vertical_area) = glider_empennage().projected_areas();
+    println!("Tail area: {:.0} cm^2 horizontal, {:.0} cm^2 vertical", horizontal_area / 100.0, vertical_area / 100.0);
+
+    for (name, part) in glider_empennage().parts(&glider_tail_boom(), &body_loft())
+    {
+        let mut tail_file = ScadFile::new();
+        tail_file.set_detail(50);
+        tail_file.add_object(part);
+        tail_file.write_to_file(format!("glider_{}_auto.scad", name));
+    }
+
+    let linkage = glider_elevator_linkage();
+    print!("{}", linkage.geometry());
+
+    let mut horn_file = ScadFile::new();
+    horn_file.set_detail(50);
+    let stabiliser = glider_stabiliser();
+    let surface_thickness = stabiliser.hinge_thickness(stabiliser.root_offset + ELEVATOR_HORN_OFFSET);
+    horn_file.add_object(linkage.horn(surface_thickness).get());
+    horn_file.write_to_file(String::from("glider_horn_auto.scad"));
+
+    check_interior();
+
     //write_result(&translation);
-    //write_result(&servo());
+    //write_result(&ServoSpec::micro_9g().keep_out(0.0));
 
     //Print the result
     //println!("{}", translation.get_code());
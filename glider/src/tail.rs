@@ -0,0 +1,331 @@
+use na;
+use scad_generator::*;
+
+use airfoil::Naca4;
+use loft::{extrude_profile, Loft};
+use hinge::Hinge;
+use CUTTER_SIZE;
+
+//Points on each side of the surface airfoils
+const AIRFOIL_POINTS: usize = 20;
+
+//Width of the root and tip slices that are hulled into a surface
+const SLICE_WIDTH: f32 = 0.01;
+
+//A carbon rod or tube going from a socket in the back of the fuselage to the tail
+pub struct TailBoom
+{
+    pub diameter: f32,
+    pub clearance: f32,
+    pub length: f32,
+
+    //How far the boom goes into the fuselage
+    pub socket_depth: f32,
+    pub wall_thickness: f32,
+}
+
+impl TailBoom
+{
+    fn fuselage_end(outer: &Loft) -> f32
+    {
+        match outer.sections.last()
+        {
+            Some(section) => section.station,
+            None => panic!("The fuselage has no sections"),
+        }
+    }
+
+    pub fn start(&self, outer: &Loft) -> f32
+    {
+        TailBoom::fuselage_end(outer) - self.socket_depth
+    }
+
+    pub fn end(&self, outer: &Loft) -> f32
+    {
+        self.start(outer) + self.length
+    }
+
+    //Cylinder along the fuselage axis
+    fn cylinder(start: f32, length: f32, diameter: f32) -> ScadObject
+    {
+        scad!(Translate(vec3(0.0, 0.0, start));
+        {
+            scad!(Cylinder(length, Diameter(diameter)))
+        })
+    }
+
+    //Adds the socket for the boom to the back of the fuselage
+    pub fn apply(&self, shell: ScadObject, outer: &Loft) -> ScadObject
+    {
+        let start = self.start(outer);
+        let bore_diameter = self.diameter + self.clearance;
+
+        let socket = scad!(Intersection;
+        {
+            TailBoom::cylinder(start - self.wall_thickness, self.socket_depth + self.wall_thickness, bore_diameter + self.wall_thickness * 2.0),
+            outer.get()
+        });
+
+        scad!(Difference;
+        {
+            scad!(Union;{shell, socket}),
+            TailBoom::cylinder(start, self.socket_depth + 1.0, bore_diameter)
+        })
+    }
+}
+
+//A tapered tail surface with a control surface behind the hinge line. The surface is
+//built with the root at x = 0 and the span along x, the leading edge of the root at the
+//origin and the chord along z.
+#[derive(Clone, Copy, Debug)]
+pub struct TailSurface
+{
+    pub root_chord: f32,
+    pub tip_chord: f32,
+    pub span: f32,
+    //How far back the leading edge of the tip is from the leading edge of the root
+    pub sweep: f32,
+    //Thickness as a fraction of the chord
+    pub thickness: f32,
+
+    //Position of the hinge line as a fraction of the chord
+    pub hinge_position: f32,
+    pub hinge_gap: f32,
+    //Distance from the root where the control surface starts, to leave room for the boom
+    //and the other surfaces
+    pub root_offset: f32,
+}
+
+impl TailSurface
+{
+    fn airfoil(&self) -> Naca4
+    {
+        Naca4 {
+            camber: 0.0,
+            camber_position: 0.0,
+            thickness: self.thickness,
+        }
+    }
+
+    fn chord_at(&self, x: f32) -> f32
+    {
+        self.root_chord + (self.tip_chord - self.root_chord) * x / self.span
+    }
+
+    fn slice(&self, x: f32, chord: f32, leading_edge: f32) -> ScadObject
+    {
+        let profile = self.airfoil().outline(chord, AIRFOIL_POINTS)
+            .into_iter()
+            .map(|(u, v)| (u + leading_edge, v))
+            .collect::<Vec<_>>();
+
+        scad!(Translate(vec3(x, 0.0, 0.0));
+        {
+            extrude_profile(&profile, SLICE_WIDTH)
+        })
+    }
+
+    pub fn shape(&self) -> ScadObject
+    {
+        scad!(Hull;
+        {
+            self.slice(0.0, self.root_chord, 0.0),
+            self.slice(self.span, self.tip_chord, self.sweep)
+        })
+    }
+
+    //Station of the hinge line at `x`
+    fn hinge_at(&self, x: f32) -> f32
+    {
+        self.sweep * x / self.span + self.chord_at(x) * self.hinge_position
+    }
+
+    //Start and end of the part of the hinge line along the control surface
+    pub fn hinge_line(&self) -> (na::Vector3<f32>, na::Vector3<f32>)
+    {
+        (
+            vec3(self.root_offset, 0.0, self.hinge_at(self.root_offset)),
+            vec3(self.span, 0.0, self.hinge_at(self.span))
+        )
+    }
+
+    //Thickness of the surface at the hinge line at `x`
+    pub fn hinge_thickness(&self, x: f32) -> f32
+    {
+        self.airfoil().thickness_at(self.hinge_position) * self.chord_at(x)
+    }
+
+    //Everything behind the hinge line moved back by `offset`, outside the root offset
+    fn hinge_cutter(&self, offset: f32) -> ScadObject
+    {
+        let block = |x: f32| {
+            scad!(Translate(vec3(x, -CUTTER_SIZE / 2.0, self.hinge_at(x) + offset));
+            {
+                scad!(Cube(vec3(SLICE_WIDTH, CUTTER_SIZE, CUTTER_SIZE)))
+            })
+        };
+
+        scad!(Hull;
+        {
+            block(self.root_offset),
+            block(self.span + 1.0)
+        })
+    }
+
+    //The part in front of the hinge line
+    pub fn fixed(&self) -> ScadObject
+    {
+        scad!(Difference;
+        {
+            self.shape(),
+            self.hinge_cutter(-self.hinge_gap / 2.0)
+        })
+    }
+
+    pub fn control_surface(&self) -> ScadObject
+    {
+        scad!(Intersection;
+        {
+            self.shape(),
+            self.hinge_cutter(self.hinge_gap / 2.0)
+        })
+    }
+
+    pub fn area(&self) -> f32
+    {
+        (self.root_chord + self.tip_chord) / 2.0 * self.span
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug)]
+pub enum TailType
+{
+    //`stabiliser` is one half of the horizontal tail
+    Conventional
+    {
+        stabiliser: TailSurface,
+        fin: TailSurface,
+    },
+    //`dihedral` is the angle of each half above the horizontal in degrees
+    VTail
+    {
+        surface: TailSurface,
+        dihedral: f32,
+    },
+}
+
+//The tail surfaces on a sleeve that is glued to the end of the boom. The trailing edges
+//of the roots line up with the end of the boom.
+pub struct Empennage
+{
+    pub tail_type: TailType,
+    pub sleeve_length: f32,
+    pub sleeve_wall: f32,
+    pub hinge: Hinge,
+}
+
+impl Empennage
+{
+    //Moves a surface to the end of the boom with its span rotated `angle` degrees up
+    //from the right side
+    fn place(surface: &TailSurface, object: ScadObject, angle: f32, boom_end: f32) -> ScadObject
+    {
+        scad!(Translate(vec3(0.0, 0.0, boom_end - surface.root_chord));
+        {
+            scad!(Rotate(angle, vec3(0.0, 0.0, 1.0));
+            {
+                object
+            })
+        })
+    }
+
+    //Splits `surface` at the hinge line and adds it to the tail. With two names the surface
+    //is mirrored to both sides and the control surfaces are named left and right
+    fn add_surface(
+        &self,
+        surface: &TailSurface,
+        angle: f32,
+        boom_end: f32,
+        names: &[&str],
+        fixed: &mut ScadObject,
+        moving: &mut Vec<(String, ScadObject)>
+    )
+    {
+        for (i, name) in names.iter().enumerate()
+        {
+            let split = self.hinge.split(surface);
+
+            let place = |object: ScadObject| {
+                let placed = Empennage::place(surface, object, angle, boom_end);
+
+                //The first of two names is the left side
+                if names.len() == 2 && i == 0
+                {
+                    scad!(Mirror(vec3(1.0, 0.0, 0.0)); {placed})
+                }
+                else
+                {
+                    placed
+                }
+            };
+
+            fixed.add_child(place(split.fixed));
+            if let Some(control_surface) = split.moving
+            {
+                moving.push((name.to_string(), place(control_surface)));
+            }
+        }
+    }
+
+    //The fixed surfaces and the sleeve as one part followed by the control surfaces,
+    //all in place at the end of the boom
+    pub fn parts(&self, boom: &TailBoom, outer: &Loft) -> Vec<(String, ScadObject)>
+    {
+        let boom_end = boom.end(outer);
+
+        let mut fixed = scad!(Union);
+        let mut moving = vec!();
+
+        match self.tail_type
+        {
+            TailType::Conventional {stabiliser, fin} => {
+                self.add_surface(&stabiliser, 0.0, boom_end, &["elevator_left", "elevator_right"], &mut fixed, &mut moving);
+                self.add_surface(&fin, 90.0, boom_end, &["rudder"], &mut fixed, &mut moving);
+            }
+            TailType::VTail {surface, dihedral} => {
+                self.add_surface(&surface, dihedral, boom_end, &["ruddervator_left", "ruddervator_right"], &mut fixed, &mut moving);
+            }
+        }
+
+        //The sleeve is closed behind the end of the boom
+        fixed.add_child(TailBoom::cylinder(
+            boom_end - self.sleeve_length,
+            self.sleeve_length + self.sleeve_wall,
+            boom.diameter + boom.clearance + self.sleeve_wall * 2.0
+        ));
+
+        let tail = scad!(Difference;
+        {
+            fixed,
+            TailBoom::cylinder(boom_end - self.sleeve_length - 1.0, self.sleeve_length + 1.0, boom.diameter + boom.clearance)
+        });
+
+        let mut result = vec!((String::from("tail"), tail));
+        result.extend(moving);
+        result
+    }
+
+    //Horizontal and vertical area of the tail, projected for a V-tail
+    pub fn projected_areas(&self) -> (f32, f32)
+    {
+        match self.tail_type
+        {
+            TailType::Conventional {stabiliser, fin} => (stabiliser.area() * 2.0, fin.area()),
+            TailType::VTail {surface, dihedral} => {
+                let angle = dihedral.to_radians();
+                (surface.area() * 2.0 * angle.cos(), surface.area() * 2.0 * angle.sin())
+            }
+        }
+    }
+}
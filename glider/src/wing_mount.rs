@@ -0,0 +1,306 @@
+use scad_generator::*;
+
+use airfoil::Naca4;
+use loft::{extrude_profile, Loft};
+use CUTTER_SIZE;
+
+//Points on each side of the airfoil outline
+const AIRFOIL_POINTS: usize = 40;
+
+//A carbon rod or tube joining the wing halves through the center of the saddle
+#[derive(Clone, Copy, Debug)]
+pub struct JoinerRod
+{
+    //Position along the root chord as a fraction of the chord. The rod is on the camber line
+    pub chord_position: f32,
+    pub diameter: f32,
+}
+
+//How the wing is held down on the saddle
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug)]
+pub enum WingRetention
+{
+    //A bolt going down through each wing root and the saddle floor into a nut below it
+    Bolt
+    {
+        diameter: f32,
+        chord_position: f32,
+    },
+    //Dowels through the top of the fuselage ahead of and behind the wing for rubber
+    //bands going over the wing
+    RubberBandDowels
+    {
+        diameter: f32,
+        //Distance from the leading and trailing edge to the dowels
+        spacing: f32,
+    },
+}
+
+//Wing saddle on top of the fuselage. The top of the fuselage is cut down to the lower
+//surface of the root airfoil between the leading and trailing edge and the wing halves
+//rest on a floor following that surface. Where the fuselage is narrower than the saddle,
+//flat cheeks go up to the floor. The halves butt against a center block with the root
+//profile that the joiner rods go across.
+pub struct WingMount
+{
+    pub airfoil: Naca4,
+    pub chord: f32,
+
+    //Station and height of the leading edge of the root
+    pub leading_edge: f32,
+    pub height: f32,
+    //Angle of the chord to the fuselage axis in degrees, positive is leading edge up
+    pub incidence: f32,
+
+    //Width of the block between the wing roots
+    pub center_width: f32,
+    //Width of the cheeks under the floor
+    pub saddle_width: f32,
+    //Thickness of the floor and the wall around the rod sockets
+    pub wall_thickness: f32,
+
+    pub rods: Vec<JoinerRod>,
+    //Extra diameter of the rod sockets
+    pub rod_clearance: f32,
+
+    pub retention: WingRetention,
+}
+
+impl WingMount
+{
+    //Moves a point in the airfoil coordinates to the side plane of the fuselage as (z, y)
+    fn to_fuselage(&self, (u, v): (f32, f32)) -> (f32, f32)
+    {
+        let (sin, cos) = self.incidence.to_radians().sin_cos();
+
+        (self.leading_edge + u * cos + v * sin, self.height - u * sin + v * cos)
+    }
+
+    fn root_profile(&self) -> Vec<(f32, f32)>
+    {
+        self.airfoil.outline(self.chord, AIRFOIL_POINTS)
+            .into_iter()
+            .map(|point| self.to_fuselage(point))
+            .collect()
+    }
+
+    //The lower surface of the root from the leading edge to the trailing edge
+    fn lower_surface(&self) -> Vec<(f32, f32)>
+    {
+        let profile = self.root_profile();
+
+        let mut result = profile[AIRFOIL_POINTS..].to_vec();
+        result.push(profile[0]);
+        result
+    }
+
+    //The lower surface closed by a straight line at `y` below it
+    fn below_lower_surface(&self, y: f32) -> Vec<(f32, f32)>
+    {
+        let lower = self.lower_surface();
+        let (front, back) = (lower[0], lower[lower.len() - 1]);
+
+        let mut result = lower;
+        result.push((back.0, y));
+        result.push((front.0, y));
+        result
+    }
+
+    //Height of the underside of the floor at station `z`, None outside the saddle
+    pub fn floor_bottom(&self, z: f32) -> Option<f32>
+    {
+        self.lower_surface()
+            .windows(2)
+            .find(|pair| z >= pair[0].0 && z <= pair[1].0)
+            .map(|pair| {
+                let t = (z - pair[0].0) / (pair[1].0 - pair[0].0);
+                pair[0].1 + (pair[1].1 - pair[0].1) * t - self.wall_thickness
+            })
+    }
+
+    //Point on the camber line at `chord_position`
+    fn camber_point(&self, chord_position: f32) -> (f32, f32)
+    {
+        self.to_fuselage((
+            chord_position * self.chord,
+            self.airfoil.camber_at(chord_position) * self.chord
+        ))
+    }
+
+    //Positions in the side plane and outside diameters of the rod sockets
+    pub fn sockets(&self) -> Vec<((f32, f32), f32)>
+    {
+        self.rods.iter()
+            .map(|rod| (
+                self.camber_point(rod.chord_position),
+                rod.diameter + self.rod_clearance + self.wall_thickness * 2.0
+            ))
+            .collect()
+    }
+
+    //Panics if the cheeks under the floor would be turned inside out or a rod socket
+    //does not fit inside the root
+    pub fn check(&self)
+    {
+        for (z, y) in self.lower_surface()
+        {
+            if y - self.wall_thickness < 0.0
+            {
+                panic!("The saddle floor at station {:.1} goes below the fuselage axis", z);
+            }
+        }
+
+        for (rod, (_, diameter)) in self.rods.iter().zip(self.sockets())
+        {
+            if self.airfoil.thickness_at(rod.chord_position) * self.chord < diameter
+            {
+                panic!("The {} mm rod socket at {} of the chord is thicker than the root", rod.diameter, rod.chord_position);
+            }
+        }
+    }
+
+    //Cylinder along x through the fuselage
+    fn cross_cylinder((z, y): (f32, f32), diameter: f32) -> ScadObject
+    {
+        scad!(Translate(vec3(-CUTTER_SIZE / 2.0, y, z));
+        {
+            scad!(Rotate(90.0, vec3(0.0, 1.0, 0.0));
+            {
+                scad!(Cylinder(CUTTER_SIZE, Diameter(diameter)))
+            })
+        })
+    }
+
+    //Half the width of the floor at `(z, y)`, the fuselage or the cheeks if they are wider
+    fn floor_half_width(&self, outer: &Loft, (z, y): (f32, f32)) -> f32
+    {
+        outer.section_at(z)
+            .and_then(|s| s.half_width_at(y))
+            .unwrap_or(0.0)
+            .max(self.saddle_width / 2.0)
+    }
+
+    fn retention_holes(&self, outer: &Loft) -> ScadObject
+    {
+        let mut result = scad!(Union);
+
+        match self.retention
+        {
+            WingRetention::Bolt {diameter, chord_position} => {
+                let (z, _) = self.camber_point(chord_position);
+                let bottom = match self.floor_bottom(z)
+                {
+                    Some(bottom) => bottom,
+                    None => panic!("The wing bolt at station {} is outside the saddle", z),
+                };
+
+                //Halfway between the center block and the edge of the floor
+                let edge = self.floor_half_width(outer, (z, bottom));
+                let x = (self.center_width / 2.0 + edge) / 2.0;
+                if x - diameter / 2.0 < self.center_width / 2.0
+                {
+                    panic!("The wing bolt at station {} does not fit beside the center block", z);
+                }
+
+                for side in &[-1.0, 1.0]
+                {
+                    //Goes a little below the floor to break through it cleanly
+                    result.add_child(scad!(Translate(vec3(side * x, bottom - 1.0, z));
+                    {
+                        scad!(Rotate(-90.0, vec3(1.0, 0.0, 0.0));
+                        {
+                            scad!(Cylinder(CUTTER_SIZE, Diameter(diameter)))
+                        })
+                    }));
+                }
+            }
+            WingRetention::RubberBandDowels {diameter, spacing} => {
+                for position in self.dowel_positions(outer, diameter, spacing)
+                {
+                    result.add_child(Self::cross_cylinder(position, diameter));
+                }
+            }
+        }
+        result
+    }
+
+    //The dowels are as high in the fuselage as their sockets fit
+    fn dowel_positions(&self, outer: &Loft, diameter: f32, spacing: f32) -> Vec<(f32, f32)>
+    {
+        let lower = self.lower_surface();
+        let stations = [lower[0].0 - spacing, lower[lower.len() - 1].0 + spacing];
+
+        stations.iter()
+            .map(|&z| {
+                let top = match outer.section_at(z)
+                {
+                    Some(section) => section.vertical_offset + section.height / 2.0,
+                    None => panic!("The wing dowel at station {} is outside the fuselage", z),
+                };
+                (z, top - diameter / 2.0 - self.wall_thickness)
+            })
+            .collect()
+    }
+
+    //Cuts the saddle into the top of the fuselage and adds the floor, cheeks, center
+    //block, rod sockets and retention
+    pub fn apply(&self, shell: ScadObject, outer: &Loft) -> ScadObject
+    {
+        self.check();
+
+        let profile = self.root_profile();
+
+        let lower = self.lower_surface();
+        let mut above = lower.clone();
+        above.push((lower[lower.len() - 1].0, CUTTER_SIZE));
+        above.push((lower[0].0, CUTTER_SIZE));
+
+        let mut floor_band = lower.clone();
+        floor_band.extend(lower.iter().rev().map(|&(z, y)| (z, y - self.wall_thickness)));
+
+        let cheeks = extrude_profile(&self.below_lower_surface(0.0), self.saddle_width);
+
+        //The floor goes across the fuselage and the cheeks
+        let floor = scad!(Intersection;
+        {
+            extrude_profile(&floor_band, CUTTER_SIZE),
+            scad!(Union;{outer.get(), cheeks.clone()}),
+        });
+
+        //Only the part of the cheeks outside the fuselage is added, the inside is left open
+        let cheeks = scad!(Difference;{cheeks, outer.get()});
+
+        let mut bores = scad!(Union);
+        for rod in &self.rods
+        {
+            bores.add_child(Self::cross_cylinder(
+                self.camber_point(rod.chord_position),
+                rod.diameter + self.rod_clearance
+            ));
+        }
+
+        let mut dowel_sockets = scad!(Union);
+        if let WingRetention::RubberBandDowels {diameter, spacing} = self.retention
+        {
+            for position in self.dowel_positions(outer, diameter, spacing)
+            {
+                dowel_sockets.add_child(Self::cross_cylinder(position, diameter + self.wall_thickness * 2.0));
+            }
+        }
+
+        scad!(Difference;
+        {
+            scad!(Union;
+            {
+                scad!(Difference;{shell, extrude_profile(&above, CUTTER_SIZE)}),
+                floor,
+                cheeks,
+                extrude_profile(&profile, self.center_width),
+                scad!(Intersection;{dowel_sockets, outer.get()}),
+            }),
+            bores,
+            self.retention_holes(outer),
+        })
+    }
+}
@@ -0,0 +1,383 @@
+use std::io::prelude::*;
+use std::fs::File;
+
+use na;
+use scad_generator::*;
+
+use interference::CollisionBox;
+
+//A named frame on a part that other parts can be attached to. `normal` points out of
+//the part and `up` is perpendicular to it and fixes the rotation around the normal.
+#[derive(Clone, Copy, Debug)]
+pub struct Anchor
+{
+    pub origin: na::Vector3<f32>,
+    pub normal: na::Vector3<f32>,
+    pub up: na::Vector3<f32>,
+}
+
+impl Anchor
+{
+    pub fn new(origin: na::Vector3<f32>, normal: na::Vector3<f32>, up: na::Vector3<f32>) -> Anchor
+    {
+        Anchor {
+            origin,
+            normal: normal.normalize(),
+            up: up.normalize(),
+        }
+    }
+
+    fn basis(&self) -> na::Matrix3<f32>
+    {
+        na::Matrix3::from_columns(&[self.normal, self.up, self.normal.cross(&self.up)])
+    }
+
+    fn transformed(&self, placement: &Placement) -> Anchor
+    {
+        Anchor {
+            origin: placement.apply(self.origin),
+            normal: placement.rotation * self.normal,
+            up: placement.rotation * self.up,
+        }
+    }
+}
+
+//A rigid transform which maps a point p to `rotation * p + translation`
+#[derive(Clone, Copy, Debug)]
+pub struct Placement
+{
+    pub rotation: na::Matrix3<f32>,
+    pub translation: na::Vector3<f32>,
+}
+
+impl Placement
+{
+    pub fn identity() -> Placement
+    {
+        Placement {
+            rotation: na::Matrix3::identity(),
+            translation: vec3(0.0, 0.0, 0.0),
+        }
+    }
+
+    //The placement that puts `child` on top of `target` facing it
+    fn mating(child: &Anchor, target: &Anchor) -> Placement
+    {
+        let flipped = Anchor {
+            origin: target.origin,
+            normal: -target.normal,
+            up: target.up,
+        };
+
+        let rotation = flipped.basis() * child.basis().transpose();
+
+        Placement {
+            rotation,
+            translation: target.origin - rotation * child.origin,
+        }
+    }
+
+    pub fn apply(&self, point: na::Vector3<f32>) -> na::Vector3<f32>
+    {
+        self.rotation * point + self.translation
+    }
+
+    //Returns the rotation as an angle in degrees around an axis
+    fn axis_angle(&self) -> (f32, na::Vector3<f32>)
+    {
+        let r = &self.rotation;
+        let cos_angle = ((r[(0, 0)] + r[(1, 1)] + r[(2, 2)] - 1.0) / 2.0).max(-1.0).min(1.0);
+        let angle = cos_angle.acos();
+
+        if angle < 1e-4
+        {
+            return (0.0, vec3(0.0, 0.0, 1.0));
+        }
+
+        let axis = if std::f32::consts::PI - angle < 1e-3
+        {
+            //The antisymmetric part vanishes at 180 degrees so the axis is read from the diagonal
+            let diagonal = vec3(r[(0, 0)], r[(1, 1)], r[(2, 2)]);
+            let axis = diagonal.map(|d| ((d + 1.0) / 2.0).max(0.0).sqrt());
+
+            if axis.x >= axis.y && axis.x >= axis.z
+            {
+                vec3(axis.x, r[(0, 1)] / (2.0 * axis.x), r[(0, 2)] / (2.0 * axis.x))
+            }
+            else if axis.y >= axis.z
+            {
+                vec3(r[(0, 1)] / (2.0 * axis.y), axis.y, r[(1, 2)] / (2.0 * axis.y))
+            }
+            else
+            {
+                vec3(r[(0, 2)] / (2.0 * axis.z), r[(1, 2)] / (2.0 * axis.z), axis.z)
+            }
+        }
+        else
+        {
+            vec3(
+                r[(2, 1)] - r[(1, 2)],
+                r[(0, 2)] - r[(2, 0)],
+                r[(1, 0)] - r[(0, 1)]
+            )
+        };
+
+        (angle.to_degrees(), axis.normalize())
+    }
+
+    pub fn transform(&self, object: ScadObject) -> ScadObject
+    {
+        let (angle, axis) = self.axis_angle();
+
+        scad!(Translate(self.translation);
+        {
+            scad!(Rotate(angle, axis);
+            {
+                object
+            })
+        })
+    }
+}
+
+pub struct Part
+{
+    pub name: String,
+    pub object: ScadObject,
+    pub color: na::Vector3<f32>,
+    pub anchors: Vec<(String, Anchor)>,
+    //Boxes covering the solid parts, used for interference checks
+    pub volumes: Vec<CollisionBox>,
+}
+
+impl Part
+{
+    pub fn new(name: &str, object: ScadObject, color: na::Vector3<f32>) -> Part
+    {
+        Part {
+            name: String::from(name),
+            object,
+            color,
+            anchors: vec!(),
+            volumes: vec!(),
+        }
+    }
+
+    pub fn with_anchor(mut self, name: &str, anchor: Anchor) -> Part
+    {
+        self.anchors.push((String::from(name), anchor));
+        self
+    }
+
+    pub fn with_volume(mut self, min: na::Vector3<f32>, size: na::Vector3<f32>) -> Part
+    {
+        self.volumes.push(CollisionBox {
+            min,
+            size,
+        });
+        self
+    }
+
+    pub fn anchor(&self, name: &str) -> Anchor
+    {
+        match self.anchors.iter().find(|&&(ref anchor_name, _)| anchor_name == name)
+        {
+            Some(&(_, anchor)) => anchor,
+            None => panic!("Part {} has no anchor named {}", self.name, name),
+        }
+    }
+}
+
+pub struct PlacedPart
+{
+    pub part: Part,
+    pub placement: Placement,
+
+    //Index of the part this one was mated to
+    pub parent: Option<usize>,
+    //Direction in which the part moves away from its parent when exploded
+    pub mating_axis: na::Vector3<f32>,
+}
+
+impl PlacedPart
+{
+    pub fn world_anchor(&self, name: &str) -> Anchor
+    {
+        self.part.anchor(name).transformed(&self.placement)
+    }
+
+    pub fn get(&self) -> ScadObject
+    {
+        scad!(Color(self.part.color);
+        {
+            self.placement.transform(self.part.object.clone())
+        })
+    }
+}
+
+//Builds an assembly by attaching parts to anchors on parts that are already placed.
+//The first part stays where it was generated.
+pub struct Assembly
+{
+    pub parts: Vec<PlacedPart>,
+}
+
+impl Assembly
+{
+    pub fn new(base: Part) -> Assembly
+    {
+        Assembly {
+            parts: vec!(PlacedPart {
+                part: base,
+                placement: Placement::identity(),
+                parent: None,
+                mating_axis: vec3(0.0, 0.0, 0.0),
+            }),
+        }
+    }
+
+    fn index_of(&self, name: &str) -> usize
+    {
+        match self.parts.iter().position(|placed| placed.part.name == name)
+        {
+            Some(index) => index,
+            None => panic!("No part named {} in the assembly", name),
+        }
+    }
+
+    pub fn find(&self, name: &str) -> &PlacedPart
+    {
+        &self.parts[self.index_of(name)]
+    }
+
+    //Places `part` so that its anchor `anchor` faces `target_anchor` on the already placed
+    //part `target`
+    pub fn mate(mut self, part: Part, anchor: &str, target: &str, target_anchor: &str) -> Assembly
+    {
+        let parent = self.index_of(target);
+        let target = self.parts[parent].world_anchor(target_anchor);
+        let placement = Placement::mating(&part.anchor(anchor), &target);
+
+        self.parts.push(PlacedPart {
+            part,
+            placement,
+            parent: Some(parent),
+            mating_axis: target.normal,
+        });
+
+        self
+    }
+
+    //Adds a part that was generated in the coordinates of the first part. It stays where
+    //it is in exploded views
+    pub fn with_part(mut self, part: Part) -> Assembly
+    {
+        self.parts.push(PlacedPart {
+            part,
+            placement: Placement::identity(),
+            parent: Some(0),
+            mating_axis: vec3(0.0, 0.0, 0.0),
+        });
+
+        self
+    }
+
+    pub fn get(&self) -> ScadObject
+    {
+        let mut result = scad!(Union);
+
+        for placed in &self.parts
+        {
+            result.add_child(placed.get());
+        }
+
+        result
+    }
+
+    pub fn write_to_file(&self, filename: &str)
+    {
+        let mut sfile = ScadFile::new();
+
+        sfile.set_detail(50);
+        sfile.add_object(self.get());
+
+        sfile.write_to_file(String::from(filename));
+    }
+
+    //Offsets of each part when every part is moved `distance` along its mating axis,
+    //relative to its parent
+    fn explode_offsets(&self, distance: f32) -> Vec<na::Vector3<f32>>
+    {
+        let mut offsets: Vec<na::Vector3<f32>> = vec!();
+
+        //Parents are always added before their children
+        for placed in &self.parts
+        {
+            let parent_offset = match placed.parent
+            {
+                Some(parent) => offsets[parent],
+                None => vec3(0.0, 0.0, 0.0),
+            };
+
+            offsets.push(parent_offset + placed.mating_axis * distance);
+        }
+
+        offsets
+    }
+
+    pub fn exploded(&self, distance: f32) -> ScadObject
+    {
+        let offsets = self.explode_offsets(distance);
+
+        let mut result = scad!(Union);
+
+        for (placed, offset) in self.parts.iter().zip(offsets.into_iter())
+        {
+            result.add_child(scad!(Translate(offset); placed.get()));
+        }
+
+        result
+    }
+
+    //Writes an animation where the parts start exploded and are moved into place one by
+    //one in the order they were mated as `$t` goes from 0 to 1
+    pub fn write_animation(&self, distance: f32, filename: &str)
+    {
+        let steps = (self.parts.len() as f32 - 1.0).max(1.0);
+
+        let mut code = String::from("$fn=50;\n");
+
+        for (i, placed) in self.parts.iter().enumerate()
+        {
+            //Each part is moved by its own step and the steps of all its parents
+            let mut terms = vec!();
+            let mut current = Some(i);
+            while let Some(index) = current
+            {
+                let ancestor = &self.parts[index];
+                if ancestor.parent.is_some()
+                {
+                    let axis = ancestor.mating_axis * distance;
+                    terms.push(format!(
+                        "[{}, {}, {}] * max(0, min(1, {} - $t * {}))",
+                        axis.x, axis.y, axis.z, index, steps
+                    ));
+                }
+                current = ancestor.parent;
+            }
+
+            let offset = if terms.is_empty()
+            {
+                String::from("[0, 0, 0]")
+            }
+            else
+            {
+                terms.join(" + ")
+            };
+
+            code.push_str(&format!("translate({})\n{{\n{}\n}}\n", offset, placed.get().get_code()));
+        }
+
+        let mut file = File::create(filename).unwrap();
+        file.write_all(code.as_bytes()).unwrap();
+    }
+}
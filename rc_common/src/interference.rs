@@ -0,0 +1,270 @@
+use std::fmt;
+
+use na;
+use scad_generator::*;
+
+use assembly::{Assembly, PlacedPart};
+
+//Parts are checked using simplified collision volumes instead of the generated geometry.
+//Each part lists a few boxes that cover its solid material in its own coordinates.
+#[derive(Clone, Copy, Debug)]
+pub struct CollisionBox
+{
+    pub min: na::Vector3<f32>,
+    pub size: na::Vector3<f32>,
+}
+
+//Surface samples per box edge when estimating clearance
+const CLEARANCE_SAMPLES: usize = 8;
+
+//Overlaps shallower than this are parts resting against each other
+const CONTACT_TOLERANCE: f32 = 0.01;
+
+//A collision box after the part has been placed in the assembly
+struct OrientedBox
+{
+    center: na::Vector3<f32>,
+    axes: [na::Vector3<f32>; 3],
+    half_size: na::Vector3<f32>,
+}
+
+impl OrientedBox
+{
+    fn new(collision_box: &CollisionBox, placed: &PlacedPart) -> OrientedBox
+    {
+        let rotation = placed.placement.rotation;
+        let half_size = collision_box.size / 2.0;
+
+        OrientedBox {
+            center: placed.placement.apply(collision_box.min + half_size),
+            axes: [
+                rotation * vec3(1.0, 0.0, 0.0),
+                rotation * vec3(0.0, 1.0, 0.0),
+                rotation * vec3(0.0, 0.0, 1.0)
+            ],
+            half_size,
+        }
+    }
+
+    //Half the length of the projection of the box on `axis`
+    fn projected_radius(&self, axis: &na::Vector3<f32>) -> f32
+    {
+        (0..3).map(|i| self.half_size[i] * self.axes[i].dot(axis).abs()).sum()
+    }
+
+    //Penetration depth found with the separating axis test, or `None` if the boxes
+    //are separated
+    fn penetration(&self, other: &OrientedBox) -> Option<f32>
+    {
+        let mut axes = vec!();
+        for i in 0..3
+        {
+            axes.push(self.axes[i]);
+            axes.push(other.axes[i]);
+
+            for j in 0..3
+            {
+                let cross = self.axes[i].cross(&other.axes[j]);
+                //Parallel edges don't give a new axis
+                if cross.norm() > 1e-4
+                {
+                    axes.push(cross.normalize());
+                }
+            }
+        }
+
+        let offset = other.center - self.center;
+
+        let mut depth = std::f32::MAX;
+        for axis in axes
+        {
+            let overlap = self.projected_radius(&axis) + other.projected_radius(&axis)
+                - offset.dot(&axis).abs();
+
+            if overlap <= 0.0
+            {
+                return None;
+            }
+            depth = depth.min(overlap);
+        }
+
+        Some(depth)
+    }
+
+    fn distance_to(&self, point: na::Vector3<f32>) -> f32
+    {
+        let offset = point - self.center;
+
+        let mut outside = 0.0;
+        for i in 0..3
+        {
+            let excess = (offset.dot(&self.axes[i]).abs() - self.half_size[i]).max(0.0);
+            outside += excess * excess;
+        }
+        outside.sqrt()
+    }
+
+    fn surface_points(&self) -> Vec<na::Vector3<f32>>
+    {
+        let mut result = vec!();
+
+        let steps = CLEARANCE_SAMPLES;
+        for face_axis in 0..3
+        {
+            let (u, v) = ((face_axis + 1) % 3, (face_axis + 2) % 3);
+
+            for side in &[-1.0, 1.0]
+            {
+                for i in 0..steps + 1
+                {
+                    for j in 0..steps + 1
+                    {
+                        let s = i as f32 / steps as f32 * 2.0 - 1.0;
+                        let t = j as f32 / steps as f32 * 2.0 - 1.0;
+
+                        result.push(self.center
+                            + self.axes[face_axis] * self.half_size[face_axis] * *side
+                            + self.axes[u] * self.half_size[u] * s
+                            + self.axes[v] * self.half_size[v] * t);
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    fn world_bounds(&self) -> (na::Vector3<f32>, na::Vector3<f32>)
+    {
+        let extent = vec3(
+            self.projected_radius(&vec3(1.0, 0.0, 0.0)),
+            self.projected_radius(&vec3(0.0, 1.0, 0.0)),
+            self.projected_radius(&vec3(0.0, 0.0, 1.0))
+        );
+
+        (self.center - extent, self.center + extent)
+    }
+}
+
+fn oriented_boxes(placed: &PlacedPart) -> Vec<OrientedBox>
+{
+    placed.part.volumes.iter()
+        .map(|volume| OrientedBox::new(volume, placed))
+        .collect()
+}
+
+pub struct Interference
+{
+    pub first: String,
+    pub second: String,
+    pub depth: f32,
+    //Approximate overlapping region as world coordinate bounds
+    pub region_min: na::Vector3<f32>,
+    pub region_max: na::Vector3<f32>,
+}
+
+impl fmt::Display for Interference
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(
+            f,
+            "{} overlaps {} by {:.2} mm between [{:.1}, {:.1}, {:.1}] and [{:.1}, {:.1}, {:.1}]",
+            self.first,
+            self.second,
+            self.depth,
+            self.region_min.x, self.region_min.y, self.region_min.z,
+            self.region_max.x, self.region_max.y, self.region_max.z
+        )
+    }
+}
+
+//Finds every pair of parts in the assembly whose collision volumes overlap
+pub fn find_interferences(assembly: &Assembly) -> Vec<Interference>
+{
+    let boxes = assembly.parts.iter().map(oriented_boxes).collect::<Vec<_>>();
+
+    let mut result = vec!();
+    for i in 0..assembly.parts.len()
+    {
+        for j in i + 1..assembly.parts.len()
+        {
+            for first in &boxes[i]
+            {
+                for second in &boxes[j]
+                {
+                    let depth = match first.penetration(second)
+                    {
+                        Some(depth) if depth > CONTACT_TOLERANCE => depth,
+                        _ => continue,
+                    };
+
+                    let (first_min, first_max) = first.world_bounds();
+                    let (second_min, second_max) = second.world_bounds();
+
+                    result.push(Interference {
+                        first: assembly.parts[i].part.name.clone(),
+                        second: assembly.parts[j].part.name.clone(),
+                        depth,
+                        region_min: vec3(
+                            first_min.x.max(second_min.x),
+                            first_min.y.max(second_min.y),
+                            first_min.z.max(second_min.z)
+                        ),
+                        region_max: vec3(
+                            first_max.x.min(second_max.x),
+                            first_max.y.min(second_max.y),
+                            first_max.z.min(second_max.z)
+                        ),
+                    });
+                }
+            }
+        }
+    }
+    result
+}
+
+//Points spread over the surfaces of the collision volumes of a placed part, for checking
+//it against shapes that aren't made of boxes
+pub fn surface_points(placed: &PlacedPart) -> Vec<na::Vector3<f32>>
+{
+    oriented_boxes(placed).iter()
+        .flat_map(|oriented| oriented.surface_points())
+        .collect()
+}
+
+//Smallest distance between the collision volumes of two parts. The distance is estimated
+//from points sampled on the surfaces so it can be slightly larger than the real distance.
+//Overlapping parts return the negative penetration depth. None if either part has no
+//collision volumes, since there is nothing to check
+pub fn clearance(assembly: &Assembly, first: &str, second: &str) -> Option<f32>
+{
+    let first_boxes = oriented_boxes(assembly.find(first));
+    let second_boxes = oriented_boxes(assembly.find(second));
+
+    if first_boxes.is_empty() || second_boxes.is_empty()
+    {
+        return None;
+    }
+
+    let mut result = std::f32::MAX;
+    for a in &first_boxes
+    {
+        for b in &second_boxes
+        {
+            if let Some(depth) = a.penetration(b)
+            {
+                result = result.min(-depth);
+                continue;
+            }
+
+            let a_to_b = a.surface_points().into_iter().map(|p| b.distance_to(p));
+            let b_to_a = b.surface_points().into_iter().map(|p| a.distance_to(p));
+
+            for distance in a_to_b.chain(b_to_a)
+            {
+                result = result.min(distance);
+            }
+        }
+    }
+    Some(result)
+}
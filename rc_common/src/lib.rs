@@ -0,0 +1,8 @@
+//Parts shared between the planes
+#[macro_use]
+extern crate scad_generator;
+extern crate nalgebra as na;
+
+pub mod servo;
+pub mod assembly;
+pub mod interference;
@@ -0,0 +1,257 @@
+use na;
+use scad_generator::*;
+
+//Dimensions of a servo in mm. The servo stands on its bottom with the output shaft
+//pointing up along z. The body is centered in x and goes from 0 to `body_size.y` in y.
+#[derive(Clone, Copy, Debug)]
+pub struct ServoSpec
+{
+    pub name: &'static str,
+
+    pub body_size: na::Vector3<f32>,
+
+    //The mounting tabs go across the whole body in x and y
+    pub tab_length: f32,
+    pub tab_thickness: f32,
+    //Height of the bottom of the tabs above the bottom of the body
+    pub tab_height: f32,
+
+    pub screw_diameter: f32,
+    //Distance between the screw center and the end of the tabs
+    pub screw_edge_offset: f32,
+
+    //The cable leaves the -x end of the body
+    pub cable_width: f32,
+    pub cable_height: f32,
+    pub cable_exit_height: f32,
+
+    //Distance from the body center to the output shaft along x
+    pub shaft_offset: f32,
+
+    //Rotation of the output shaft from the center to each side in degrees
+    pub travel: f32,
+    //Stall torque in Nmm
+    pub torque: f32,
+}
+
+//Length of the screws that are cut into the mount
+const SCREW_LENGTH: f32 = 10.0;
+
+//Length of the channel for the cable outside the body
+const CABLE_CHANNEL_LENGTH: f32 = 10.0;
+
+impl ServoSpec
+{
+    //The 9 gram servo that the glider was first designed around
+    pub fn micro_9g() -> ServoSpec
+    {
+        ServoSpec {
+            name: "micro_9g",
+            body_size: vec3(24.0, 13.0, 22.0),
+            tab_length: 33.0,
+            tab_thickness: 2.5,
+            tab_height: 16.0,
+            screw_diameter: 3.0,
+            screw_edge_offset: 2.0,
+            cable_width: 4.0,
+            cable_height: 1.5,
+            cable_exit_height: 4.0,
+            shaft_offset: 6.0,
+            travel: 45.0,
+            torque: 157.0,
+        }
+    }
+
+    pub fn mini() -> ServoSpec
+    {
+        ServoSpec {
+            name: "mini",
+            body_size: vec3(30.0, 12.0, 30.0),
+            tab_length: 40.0,
+            tab_thickness: 2.5,
+            tab_height: 20.0,
+            screw_diameter: 2.5,
+            screw_edge_offset: 2.5,
+            cable_width: 4.0,
+            cable_height: 1.5,
+            cable_exit_height: 4.0,
+            shaft_offset: 8.0,
+            travel: 45.0,
+            torque: 245.0,
+        }
+    }
+
+    pub fn standard() -> ServoSpec
+    {
+        ServoSpec {
+            name: "standard",
+            body_size: vec3(40.0, 20.0, 38.0),
+            tab_length: 54.0,
+            tab_thickness: 2.5,
+            tab_height: 27.0,
+            screw_diameter: 4.0,
+            screw_edge_offset: 3.0,
+            cable_width: 4.5,
+            cable_height: 2.0,
+            cable_exit_height: 5.0,
+            shaft_offset: 10.0,
+            travel: 45.0,
+            torque: 402.0,
+        }
+    }
+
+    //Thin servos made to fit inside wings
+    pub fn slim_wing() -> ServoSpec
+    {
+        ServoSpec {
+            name: "slim_wing",
+            body_size: vec3(23.5, 8.0, 16.8),
+            tab_length: 30.0,
+            tab_thickness: 1.5,
+            tab_height: 11.0,
+            screw_diameter: 2.0,
+            screw_edge_offset: 1.8,
+            cable_width: 3.5,
+            cable_height: 1.2,
+            cable_exit_height: 3.0,
+            shaft_offset: 6.5,
+            travel: 40.0,
+            torque: 118.0,
+        }
+    }
+
+    pub fn catalog() -> Vec<ServoSpec>
+    {
+        vec!(
+            ServoSpec::micro_9g(),
+            ServoSpec::mini(),
+            ServoSpec::standard(),
+            ServoSpec::slim_wing(),
+        )
+    }
+
+    pub fn by_name(name: &str) -> Option<ServoSpec>
+    {
+        ServoSpec::catalog().into_iter().find(|spec| spec.name == name)
+    }
+
+    fn tab_size(&self) -> na::Vector3<f32>
+    {
+        vec3(self.tab_length, self.body_size.y, self.tab_thickness)
+    }
+
+    //Minimum corner and size of the boxes making up the body and the tabs
+    pub fn boxes(&self) -> [(na::Vector3<f32>, na::Vector3<f32>); 2]
+    {
+        [
+            (vec3(-self.body_size.x / 2.0, 0.0, 0.0), self.body_size),
+            (vec3(-self.tab_length / 2.0, 0.0, self.tab_height), self.tab_size()),
+        ]
+    }
+
+    //X position of the two mounting screws
+    pub fn screw_positions(&self) -> [f32; 2]
+    {
+        let x_offset = self.tab_length / 2.0 - self.screw_edge_offset;
+        [-x_offset, x_offset]
+    }
+
+    fn screws(&self, diameter: f32) -> ScadObject
+    {
+        let mut result = scad!(Translate(vec3(0.0, self.body_size.y / 2.0, self.tab_height - SCREW_LENGTH / 2.0)));
+
+        for x in &self.screw_positions()
+        {
+            result.add_child(scad!(Translate(vec3(*x, 0.0, 0.0));
+            {
+                scad!(Cylinder(SCREW_LENGTH, Diameter(diameter)))
+            }));
+        }
+        result
+    }
+
+    //Box around a cube of `size` at `min` which is `clearance` larger on all sides
+    fn padded_cube(min: na::Vector3<f32>, size: na::Vector3<f32>, clearance: f32) -> ScadObject
+    {
+        let padding = vec3(clearance, clearance, clearance);
+
+        scad!(Translate(min - padding);
+        {
+            scad!(Cube(size + padding * 2.0))
+        })
+    }
+
+    //Holes for self tapping screws which bite into a slightly smaller hole
+    pub fn pilot_holes(&self) -> ScadObject
+    {
+        self.screws(self.screw_diameter * 0.8)
+    }
+
+    //The space taken up by the servo, its screws and its cable, made `clearance` larger
+    //than the servo. Meant to be subtracted from the part the servo is mounted in
+    pub fn keep_out(&self, clearance: f32) -> ScadObject
+    {
+        scad!(Union;{self.envelope(clearance), self.screws(self.screw_diameter)})
+    }
+
+    //Like `keep_out` but without the screws
+    pub fn envelope(&self, clearance: f32) -> ScadObject
+    {
+        let body = ServoSpec::padded_cube(
+            vec3(-self.body_size.x / 2.0, 0.0, 0.0),
+            self.body_size,
+            clearance
+        );
+
+        let tabs = ServoSpec::padded_cube(
+            vec3(-self.tab_length / 2.0, 0.0, self.tab_height),
+            self.tab_size(),
+            clearance
+        );
+
+        let cable = ServoSpec::padded_cube(
+            vec3(
+                -self.body_size.x / 2.0 - CABLE_CHANNEL_LENGTH,
+                (self.body_size.y - self.cable_width) / 2.0,
+                self.cable_exit_height - self.cable_height / 2.0
+            ),
+            vec3(CABLE_CHANNEL_LENGTH, self.cable_width, self.cable_height),
+            clearance
+        );
+
+        scad!(Union;{body, tabs, cable})
+    }
+
+    //The outside of the mounting frame
+    pub fn frame_outline(&self, wall_thickness: f32, frame_height: f32) -> ScadObject
+    {
+        scad!(Translate(vec3(
+                    -self.tab_length / 2.0,
+                    -wall_thickness,
+                    self.tab_height - frame_height
+                ));
+        {
+            scad!(Cube(vec3(self.tab_length, self.body_size.y + wall_thickness * 2.0, frame_height)))
+        })
+    }
+
+    //A printed frame that the servo tabs are screwed onto. The top of the frame is
+    //at the bottom of the tabs
+    pub fn mounting_frame(&self, wall_thickness: f32, frame_height: f32) -> ScadObject
+    {
+        let clearance = 0.3;
+
+        let body = ServoSpec::padded_cube(
+            vec3(-self.body_size.x / 2.0, 0.0, 0.0),
+            self.body_size,
+            clearance
+        );
+
+        scad!(Difference;
+        {
+            self.frame_outline(wall_thickness, frame_height),
+            body,
+            self.pilot_holes()
+        })
+    }
+}